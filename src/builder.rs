@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use serenity::all::{ChannelId, GuildId};
+
+use crate::{
+    GuildlyHandler,
+    commands::{self, GuildlyCommand, GuildlyComponent, GuildlyModal},
+    database::Database,
+    middleware::Middleware,
+    sync::Scope,
+};
+
+/// Configures a [`GuildlyHandler`], e.g. to mount the directory inside another bot.
+#[must_use]
+pub struct GuildlyHandlerBuilder {
+    database: Database,
+    default_commands: bool,
+    command_prefix: String,
+    commands: Vec<Box<dyn GuildlyCommand>>,
+    components: Vec<Box<dyn GuildlyComponent>>,
+    modals: Vec<Box<dyn GuildlyModal>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    maintainer_channel: Option<ChannelId>,
+    dev_guild: Option<GuildId>,
+}
+
+impl GuildlyHandlerBuilder {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            default_commands: true,
+            command_prefix: String::new(),
+            commands: Vec::new(),
+            components: Vec::new(),
+            modals: Vec::new(),
+            middleware: Vec::new(),
+            maintainer_channel: None,
+            dev_guild: None,
+        }
+    }
+
+    /// Whether the builtin commands are registered. The builtin components and modals they rely
+    /// on are always registered.
+    pub fn default_commands(mut self, default_commands: bool) -> Self {
+        self.default_commands = default_commands;
+        self
+    }
+
+    /// Prepended to the name of every command, e.g. `dir-` registers `/dir-search`, so that
+    /// Guildly's commands do not clash with the host bot's.
+    pub fn command_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.command_prefix = prefix.into();
+        self
+    }
+
+    pub fn command(mut self, command: Box<dyn GuildlyCommand>) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn component(mut self, component: Box<dyn GuildlyComponent>) -> Self {
+        self.components.push(component);
+        self
+    }
+
+    pub fn modal(mut self, modal: Box<dyn GuildlyModal>) -> Self {
+        self.modals.push(modal);
+        self
+    }
+
+    /// Adds middleware run around every command, after the default ones.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets the channel where suggestions are posted for review and curators are alerted about
+    /// reported invites. Curators are the members with
+    /// [`CURATOR_PERMISSIONS`](crate::commands::CURATOR_PERMISSIONS) in its server.
+    pub fn maintainer_channel(mut self, channel: ChannelId) -> Self {
+        self.maintainer_channel = Some(channel);
+        self
+    }
+
+    /// Registers commands in `guild` instead of globally, so changes show up instantly. Without a
+    /// [maintainer channel](Self::maintainer_channel), curators are looked up in this guild.
+    pub fn dev_guild(mut self, guild: GuildId) -> Self {
+        self.dev_guild = Some(guild);
+        self
+    }
+
+    pub fn build(self) -> GuildlyHandler {
+        let mut handler = GuildlyHandler::new(self.database);
+        handler.command_prefix = self.command_prefix;
+        handler.maintainer_channel = self.maintainer_channel;
+        handler.scope = self.dev_guild.map_or(Scope::Global, Scope::Guild);
+        handler.middleware.extend(self.middleware);
+
+        let builtin = if self.default_commands {
+            commands::builtin_commands()
+        } else {
+            Vec::new()
+        };
+        for command in builtin.into_iter().chain(self.commands) {
+            handler.register(command);
+        }
+        for component in commands::builtin_components()
+            .into_iter()
+            .chain(self.components)
+        {
+            handler.register_component(component);
+        }
+        for modal in commands::builtin_modals().into_iter().chain(self.modals) {
+            handler.register_modal(modal);
+        }
+        handler
+    }
+}
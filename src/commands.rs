@@ -1,23 +1,179 @@
 use serenity::{
-    all::{CommandInteraction, Context, CreateCommand, CreateEmbed},
+    all::{
+        CommandInteraction, ComponentInteraction, CreateCommand, CreateInteractionResponse, Http,
+        InstallationContext, InteractionContext, ModalInteraction, Permissions,
+    },
     async_trait,
 };
 
-use crate::GuildlyHandler;
+use std::sync::Arc;
+
+use crate::{
+    GuildlyHandler, i18n, middleware::Middleware, rate_limit::RateLimits, response::Response,
+};
+
+/// The permissions members need by default to run commands that change the directory. Server
+/// admins can grant them to other members or roles in the server's integration settings.
+pub const CURATOR_PERMISSIONS: Permissions = Permissions::MANAGE_GUILD;
 
 #[async_trait]
 pub trait GuildlyCommand: Sync + Send {
     fn name(&self) -> &'static str;
     fn create_command(&self, ctx: CreateCommand) -> CreateCommand;
+
+    /// The definition registered with Discord.
+    fn definition(&self) -> CreateCommand {
+        create_definition(self, self.name())
+    }
+
+    /// Runs the command. A single embed converts into a [`Response`] with `.into()`.
     async fn execute(
         &self,
         handler: &GuildlyHandler,
-        ctx: &Context,
+        http: &Http,
         interaction: &CommandInteraction,
-    ) -> CreateEmbed;
+    ) -> Response;
+
+    /// Whether the command usually takes longer than Discord's 3 second deadline, so that its
+    /// interaction is deferred right away instead of after
+    /// [`DEFER_AFTER`](crate::response::DEFER_AFTER).
+    fn slow(&self) -> bool {
+        false
+    }
+
+    /// Whether the "thinking" message shown while deferred is only visible to the user. It
+    /// cannot be changed afterwards, so it should match the ephemeral flag of the reply.
+    fn ephemeral(&self) -> bool {
+        false
+    }
+
+    /// Whether users may add the app to their account to run this command anywhere, including
+    /// DMs and servers the bot is not in. Replies there are always ephemeral.
+    fn user_installable(&self) -> bool {
+        false
+    }
+
+    /// The permissions members need to see and run this command, unless a server admin changes
+    /// them. `None` lets everyone run it. Commands with permissions are only available in
+    /// servers.
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        None
+    }
+
+    /// How often users may run this command.
+    fn rate_limits(&self) -> RateLimits {
+        RateLimits::default()
+    }
+
+    /// Middleware run around this command only, after the handler's own.
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        Vec::new()
+    }
+}
+
+/// Creates the definition of `command` registered as `name`, with the installs it is available
+/// through.
+pub fn create_definition<C: GuildlyCommand + ?Sized>(command: &C, name: &str) -> CreateCommand {
+    let definition = i18n::localize_command(
+        command.create_command(CreateCommand::new(name)),
+        command.name(),
+        name,
+    );
+    if let Some(permissions) = command.default_member_permissions() {
+        // Permissions don't apply in DMs, so restricted commands are only offered in servers.
+        return definition
+            .default_member_permissions(permissions)
+            .integration_types(vec![InstallationContext::Guild])
+            .contexts(vec![InteractionContext::Guild]);
+    }
+    if command.user_installable() {
+        definition
+            .integration_types(vec![InstallationContext::Guild, InstallationContext::User])
+            .contexts(vec![
+                InteractionContext::Guild,
+                InteractionContext::BotDm,
+                InteractionContext::PrivateChannel,
+            ])
+    } else {
+        definition.integration_types(vec![InstallationContext::Guild])
+    }
+}
+
+/// Handles clicks on message components whose custom ID is `guildly:<prefix()>:<args>`.
+#[async_trait]
+pub trait GuildlyComponent: Sync + Send {
+    fn prefix(&self) -> &'static str;
+
+    /// How often users may click this component.
+    fn rate_limits(&self) -> RateLimits {
+        RateLimits::default()
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &ComponentInteraction,
+    ) -> CreateInteractionResponse;
+}
+
+/// Handles submissions of modals whose custom ID is `guildly:<prefix()>:<args>`.
+#[async_trait]
+pub trait GuildlyModal: Sync + Send {
+    fn prefix(&self) -> &'static str;
+
+    /// How often users may submit this modal.
+    fn rate_limits(&self) -> RateLimits {
+        RateLimits::default()
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &ModalInteraction,
+    ) -> CreateInteractionResponse;
+}
+
+/// Every command Guildly ships with.
+pub fn builtin_commands() -> Vec<Box<dyn GuildlyCommand>> {
+    vec![
+        Box::new(add::AddServer),
+        Box::new(add::AddServerMenu),
+        Box::new(edit::EditServer),
+        Box::new(remove::RemoveServer),
+        Box::new(search::SearchServer),
+        Box::new(show_menu::ShowServersMenu),
+        Box::new(wanted::WantedServers),
+        Box::new(suggest::SuggestServer),
+        Box::new(tag::TagServer),
+        Box::new(browse::BrowseServers),
+    ]
+}
+
+/// Every component handler Guildly ships with. They are registered regardless of the enabled
+/// commands, so that buttons on older messages keep working.
+pub fn builtin_components() -> Vec<Box<dyn GuildlyComponent>> {
+    vec![
+        Box::new(remove::RemoveConfirmation),
+        Box::new(suggest::SuggestionReview),
+        Box::new(report::ReportButton),
+        Box::new(browse::BrowseSelection),
+    ]
+}
+
+/// Every modal handler Guildly ships with.
+pub fn builtin_modals() -> Vec<Box<dyn GuildlyModal>> {
+    vec![Box::new(add::AddServerModal), Box::new(report::ReportModal)]
 }
 
 pub mod add;
+pub mod browse;
+pub mod edit;
 pub mod remove;
+pub mod report;
 pub mod search;
 pub mod show_menu;
+pub mod suggest;
+pub mod tag;
+pub mod wanted;
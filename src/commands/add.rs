@@ -1,115 +1,255 @@
+use std::sync::Arc;
+
 use serenity::{
     all::{
-        CommandInteraction, CommandOptionType, CommandType, Context, CreateCommand,
-        CreateCommandOption, CreateEmbed, ResolvedValue,
+        ActionRowComponent, CommandInteraction, CommandType, CreateActionRow, CreateCommand,
+        CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage,
+        CreateModal, Http, InputTextStyle, ModalInteraction, Permissions,
     },
     async_trait,
 };
 use url::Url;
 
-use crate::{Color, GuildEntry, GuildlyHandler, commands::GuildlyCommand, create_embed_from_entry};
+use crate::{
+    Color, GuildlyHandler, command_options,
+    commands::{CURATOR_PERMISSIONS, GuildlyCommand, GuildlyModal},
+    create_embed_from_entry, custom_id,
+    i18n::Locale,
+    link::find_guild_ids,
+    middleware::{CuratorsOnly, Middleware},
+    options::CommandOptions,
+    response::Response,
+    tr,
+    validation::{EntryInput, ValidationError, parse_guild_id, parse_icon_url, parse_invite_url},
+};
 
 pub struct AddServer;
 
+command_options! {
+    struct AddOptions {
+        guild_id: optional u64 = "id", "Server Id";
+        name: optional String = "name", "Guild Name";
+        icon_url: optional Url = "icon", "Icon Url";
+        invite_url: optional Url = "invite", "Invite Url";
+        description: optional String = "description", "Description";
+        language: optional String = "language", "Main language, e.g. en or ja";
+        tags: optional String = "tags", "Comma separated tags";
+        nsfw: optional bool = "nsfw", "Whether the server is age-restricted";
+        banner_url: optional Url = "banner", "Banner Url";
+        overwrite: optional bool = "overwrite", "Replace the existing entry";
+    }
+}
+
+/// Validates `input` and stores the entry, refusing to replace an existing one unless
+/// `overwrite` is set.
+fn add_entry(
+    handler: &GuildlyHandler,
+    input: &EntryInput,
+    overwrite: bool,
+    locale: Locale,
+) -> CreateEmbed {
+    let entry = match input.validate() {
+        Ok(entry) => entry,
+        Err(err) => return err.create_embed(locale),
+    };
+
+    if !overwrite && let Some(existing) = handler.database.get(entry.guild_id).unwrap() {
+        return create_embed_from_entry(&existing, locale)
+            .color(Color::WARN)
+            .title(tr!(locale, "add.already_exists"))
+            .description(tr!(locale, "add.already_exists_hint"));
+    }
+
+    let _ = handler.database.insert(&entry).unwrap();
+    create_embed_from_entry(&entry, locale).title(tr!(locale, "add.added"))
+}
+
+/// Creates the add form, with the server ID field filled in if known.
+fn create_add_modal(guild_id: Option<u64>, locale: Locale) -> CreateModal {
+    let input = |style, label, custom_id| CreateInputText::new(style, label, custom_id);
+    let mut id_input = input(InputTextStyle::Short, tr!(locale, "add.form.id"), "id");
+    if let Some(guild_id) = guild_id {
+        id_input = id_input.value(guild_id.to_string());
+    }
+
+    CreateModal::new(custom_id("add", "form"), tr!(locale, "add.form.title")).components(vec![
+        CreateActionRow::InputText(id_input),
+        CreateActionRow::InputText(input(
+            InputTextStyle::Short,
+            tr!(locale, "add.form.name"),
+            "name",
+        )),
+        CreateActionRow::InputText(
+            input(
+                InputTextStyle::Short,
+                tr!(locale, "add.form.invite"),
+                "invite",
+            )
+            .required(false),
+        ),
+        CreateActionRow::InputText(
+            input(InputTextStyle::Short, tr!(locale, "add.form.icon"), "icon").required(false),
+        ),
+        CreateActionRow::InputText(
+            input(
+                InputTextStyle::Paragraph,
+                tr!(locale, "add.form.description"),
+                "description",
+            )
+            .required(false),
+        ),
+    ])
+}
+
 #[async_trait]
 impl GuildlyCommand for AddServer {
     fn name(&self) -> &'static str {
         "add"
     }
+
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        Some(CURATOR_PERMISSIONS)
+    }
+
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        vec![Arc::new(CuratorsOnly)]
+    }
     fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
         ctx.kind(CommandType::ChatInput)
-            .description("Add a server")
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "id", "Server Id")
-                    .required(true),
-            )
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "name", "Guild Name")
-                    .required(true),
-            )
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "icon", "Icon Url")
-                    .required(false),
-            )
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "invite", "Invite Url")
-                    .required(false),
-            )
+            .description("Add a server, or open the add form when no options are given")
+            .set_options(AddOptions::create_options())
     }
 
     async fn execute(
         &self,
         handler: &GuildlyHandler,
-        _ctx: &Context,
+        _http: &Http,
         interaction: &CommandInteraction,
-    ) -> CreateEmbed {
-        let mut guild_id = None;
-        let mut guild_name = None;
-        let mut guild_icon = None;
-        let mut guild_invite = None;
-        for option in interaction.data.options() {
-            match option.name {
-                "id" => {
-                    let ResolvedValue::String(option) = option.value else {
-                        unreachable!()
-                    };
-                    let Ok(parsed) = option.parse::<u64>() else {
-                        return CreateEmbed::new()
-                            .color(Color::ERROR)
-                            .title("Invalid Server ID");
-                    };
-                    guild_id = Some(parsed)
-                }
-                "name" => {
-                    let ResolvedValue::String(option) = option.value else {
-                        unreachable!()
-                    };
-                    guild_name = Some(option)
-                }
-                "icon" => {
-                    let ResolvedValue::String(option) = option.value else {
-                        unreachable!()
-                    };
-                    let Ok(icon_url) = Url::parse(option) else {
-                        return CreateEmbed::new()
-                            .color(Color::ERROR)
-                            .title("Invalid Icon URL");
-                    };
-                    guild_icon = Some(icon_url)
-                }
-                "invite" => {
-                    let ResolvedValue::String(option) = option.value else {
-                        unreachable!()
-                    };
-                    let Ok(invite_url) = Url::parse(option) else {
-                        return CreateEmbed::new()
-                            .color(Color::ERROR)
-                            .title("Invalid Icon URL");
-                    };
-                    guild_invite = Some(invite_url)
-                }
-                _ => {}
-            }
+    ) -> Response {
+        let locale = Locale::of(interaction);
+        if interaction.data.options.is_empty() {
+            return create_add_modal(None, locale).into();
         }
 
-        let Some(name) = guild_name else {
-            return CreateEmbed::new()
-                .color(Color::ERROR)
-                .title("No Guild Name");
+        let options = match AddOptions::from_interaction(interaction) {
+            Ok(options) => options,
+            Err(err) => return err.create_embed(locale).into(),
         };
-
-        let Some(guild_id) = guild_id else {
-            return CreateEmbed::new().color(Color::ERROR).title("No Guild ID");
+        let input = EntryInput {
+            guild_id: options.guild_id,
+            name: options.name.as_deref(),
+            icon_url: options.icon_url,
+            invite_url: options.invite_url,
+            description: options.description.as_deref(),
+            language: options.language.as_deref(),
+            tags: options.tags.as_deref(),
+            nsfw: options.nsfw.unwrap_or(false),
+            banner_url: options.banner_url,
         };
 
-        let entry = GuildEntry {
-            name: name.to_string(),
-            guild_id,
-            invite_url: guild_invite,
-            icon_url: guild_icon,
-        };
+        add_entry(handler, &input, options.overwrite.unwrap_or(false), locale).into()
+    }
+}
 
-        let _ = handler.database.insert(&entry).unwrap();
-        create_embed_from_entry(&entry).title("Server Added")
+/// The "Add this server" message context menu, which opens the add form for the first server
+/// linked from the message.
+pub struct AddServerMenu;
+
+#[async_trait]
+impl GuildlyCommand for AddServerMenu {
+    fn name(&self) -> &'static str {
+        "Add this server"
+    }
+
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        Some(CURATOR_PERMISSIONS)
+    }
+
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        vec![Arc::new(CuratorsOnly)]
+    }
+
+    fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
+        ctx.kind(CommandType::Message)
+    }
+
+    async fn execute(
+        &self,
+        _handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        let guild_id = interaction
+            .data
+            .resolved
+            .messages
+            .values()
+            .find_map(|message| find_guild_ids(&message.content).next());
+
+        create_add_modal(guild_id, Locale::of(interaction)).into()
+    }
+}
+
+/// Reads and parses the fields of a submitted add form.
+fn read_form(interaction: &ModalInteraction) -> Result<EntryInput<'_>, ValidationError> {
+    let field = |custom_id: &str| {
+        interaction
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(text) if text.custom_id == custom_id => {
+                    text.value.as_deref().filter(|value| !value.is_empty())
+                }
+                _ => None,
+            })
+    };
+
+    Ok(EntryInput {
+        guild_id: field("id").map(parse_guild_id).transpose()?,
+        name: field("name"),
+        icon_url: field("icon").map(parse_icon_url).transpose()?,
+        invite_url: field("invite").map(parse_invite_url).transpose()?,
+        description: field("description"),
+        ..Default::default()
+    })
+}
+
+/// Handles submissions of the form opened by [`AddServer`] and [`AddServerMenu`].
+pub struct AddServerModal;
+
+#[async_trait]
+impl GuildlyModal for AddServerModal {
+    fn prefix(&self) -> &'static str {
+        "add"
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &ModalInteraction,
+    ) -> CreateInteractionResponse {
+        let locale = Locale::of(interaction);
+        // The form is opened by `/add`, but its submissions are checked like the command.
+        let member = interaction.member.as_ref();
+        if !handler.is_curator(http, interaction.guild_id, member).await {
+            return CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .add_embed(
+                        CreateEmbed::new()
+                            .color(Color::ERROR)
+                            .title(tr!(locale, "curator.denied"))
+                            .description(tr!(locale, "curator.denied_hint")),
+                    )
+                    .ephemeral(true),
+            );
+        }
+        let embed = match read_form(interaction) {
+            Ok(input) => add_entry(handler, &input, false, locale),
+            Err(err) => err.create_embed(locale),
+        };
+        CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().add_embed(embed))
     }
 }
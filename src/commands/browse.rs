@@ -0,0 +1,192 @@
+use serenity::{
+    all::{
+        ButtonStyle, CommandInteraction, CommandType, ComponentInteraction,
+        ComponentInteractionDataKind, CreateActionRow, CreateButton, CreateCommand, CreateEmbed,
+        CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu,
+        CreateSelectMenuKind, CreateSelectMenuOption, Http,
+    },
+    async_trait,
+};
+
+use crate::{
+    Color, ENTRIES_PER_PAGE, GuildlyHandler,
+    commands::{GuildlyCommand, GuildlyComponent, report::create_report_buttons},
+    create_embed_from_entries, create_embed_from_page, custom_id, custom_id_args,
+    i18n::Locale,
+    page_count,
+    response::{Reply, Response},
+    tr,
+};
+
+/// Select menus hold at most 25 options, so only the most used tags are offered.
+const MAX_TAGS: usize = 25;
+
+pub struct BrowseServers;
+
+/// Creates the menu of the most used tags, with `selected` chosen.
+fn create_tag_menu(
+    tags: &[(String, u64)],
+    selected: Option<&str>,
+    locale: Locale,
+) -> CreateActionRow {
+    let options = tags
+        .iter()
+        .take(MAX_TAGS)
+        .map(|(tag, entries)| {
+            CreateSelectMenuOption::new(tag, tag)
+                .description(tr!(locale, "browse.entries", entries = entries))
+                .default_selection(selected == Some(tag.as_str()))
+        })
+        .collect();
+    CreateActionRow::SelectMenu(
+        CreateSelectMenu::new(
+            custom_id("browse", "tag"),
+            CreateSelectMenuKind::String { options },
+        )
+        .placeholder(tr!(locale, "browse.placeholder")),
+    )
+}
+
+#[async_trait]
+impl GuildlyCommand for BrowseServers {
+    fn name(&self) -> &'static str {
+        "browse"
+    }
+
+    fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
+        ctx.kind(CommandType::ChatInput)
+            .description("Browse servers by category")
+    }
+
+    fn ephemeral(&self) -> bool {
+        true
+    }
+
+    fn user_installable(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        let locale = Locale::of(interaction);
+        let tags = handler.database.tags().unwrap();
+        if tags.is_empty() {
+            return Reply::new()
+                .embed(
+                    CreateEmbed::new()
+                        .color(Color::INFO)
+                        .title(tr!(locale, "tag.none")),
+                )
+                .ephemeral(true)
+                .into();
+        }
+
+        Reply::new()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::INFO)
+                    .title(tr!(locale, "browse.title")),
+            )
+            .components(vec![create_tag_menu(&tags, None, locale)])
+            .ephemeral(true)
+            .into()
+    }
+}
+
+/// Handles the tag menu and page buttons attached by [`BrowseServers`].
+pub struct BrowseSelection;
+
+#[async_trait]
+impl GuildlyComponent for BrowseSelection {
+    fn prefix(&self) -> &'static str {
+        "browse"
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &ComponentInteraction,
+    ) -> CreateInteractionResponse {
+        let locale = Locale::of(interaction);
+        // Menu choices carry the tag in their value, buttons in their custom ID after the page.
+        let mut parts = custom_id_args(&interaction.data.custom_id).splitn(3, ':');
+        let selection = match (parts.next(), &interaction.data.kind) {
+            (Some("tag"), ComponentInteractionDataKind::StringSelect { values }) => {
+                values.first().map(|tag| (tag.as_str(), 0))
+            }
+            (Some("page"), _) => match (parts.next().map(str::parse::<usize>), parts.next()) {
+                (Some(Ok(page)), Some(tag)) => Some((tag, page)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((tag, page)) = selection else {
+            return CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .add_embed(
+                        CreateEmbed::new()
+                            .color(Color::ERROR)
+                            .title(tr!(locale, "browse.invalid")),
+                    )
+                    .ephemeral(true),
+            );
+        };
+
+        let tags = handler.database.tags().unwrap();
+        let entries = handler.database.tagged(tag).unwrap();
+        // A select menu needs at least one option, so drop it once every tag is gone.
+        let mut components = if tags.is_empty() {
+            Vec::new()
+        } else {
+            vec![create_tag_menu(&tags, Some(tag), locale)]
+        };
+        if entries.is_empty() {
+            return update_message(create_embed_from_entries(&[], locale), components);
+        }
+
+        let pages = page_count(&entries);
+        let page = page.min(pages - 1);
+        if pages > 1 {
+            components.push(CreateActionRow::Buttons(vec![
+                CreateButton::new(custom_id(
+                    "browse",
+                    format!("page:{}:{tag}", page.saturating_sub(1)),
+                ))
+                .label(tr!(locale, "browse.previous"))
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 0),
+                CreateButton::new(custom_id("browse", format!("page:{}:{tag}", page + 1)))
+                    .label(tr!(locale, "browse.next"))
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page + 1 >= pages),
+            ]));
+        }
+        let page_entries = entries
+            .chunks(ENTRIES_PER_PAGE)
+            .nth(page)
+            .unwrap_or_default();
+        components.extend(create_report_buttons(page_entries, locale));
+        let embed = create_embed_from_page(&entries, page, locale).title(tr!(
+            locale,
+            "browse.tagged",
+            tag = tag
+        ));
+        update_message(embed, components)
+    }
+}
+
+fn update_message(
+    embed: CreateEmbed,
+    components: Vec<CreateActionRow>,
+) -> CreateInteractionResponse {
+    CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(components),
+    )
+}
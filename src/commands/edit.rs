@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use serenity::{
+    all::{CommandInteraction, CommandType, CreateCommand, CreateEmbed, Http, Permissions},
+    async_trait,
+};
+use url::Url;
+
+use crate::{
+    Color, GuildlyHandler, command_options,
+    commands::{CURATOR_PERMISSIONS, GuildlyCommand},
+    create_embed_from_entry,
+    i18n::{self, Locale},
+    middleware::{CuratorsOnly, Middleware},
+    options::CommandOptions,
+    response::Response,
+    tr,
+    validation::PatchInput,
+};
+
+pub struct EditServer;
+
+command_options! {
+    struct EditOptions {
+        guild_id: required u64 = "id", "Server Id";
+        name: optional String = "name", "Guild Name";
+        icon_url: optional Url = "icon", "Icon Url";
+        invite_url: optional Url = "invite", "Invite Url";
+        description: optional String = "description", "Description";
+        language: optional String = "language", "Main language, e.g. en or ja";
+        tags: optional String = "tags", "Comma separated tags";
+        nsfw: optional bool = "nsfw", "Whether the server is age-restricted";
+        banner_url: optional Url = "banner", "Banner Url";
+        clear: optional String = "clear", "Field to clear", |option| {
+            option
+                .add_string_choice_localized(
+                    "Icon Url",
+                    "icon",
+                    i18n::localizations("choices.clear.icon"),
+                )
+                .add_string_choice_localized(
+                    "Invite Url",
+                    "invite",
+                    i18n::localizations("choices.clear.invite"),
+                )
+                .add_string_choice_localized(
+                    "Description",
+                    "description",
+                    i18n::localizations("choices.clear.description"),
+                )
+                .add_string_choice_localized(
+                    "Language",
+                    "language",
+                    i18n::localizations("choices.clear.language"),
+                )
+                .add_string_choice_localized(
+                    "Tags",
+                    "tags",
+                    i18n::localizations("choices.clear.tags"),
+                )
+                .add_string_choice_localized(
+                    "Banner Url",
+                    "banner",
+                    i18n::localizations("choices.clear.banner"),
+                )
+        };
+    }
+}
+
+#[async_trait]
+impl GuildlyCommand for EditServer {
+    fn name(&self) -> &'static str {
+        "edit"
+    }
+
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        Some(CURATOR_PERMISSIONS)
+    }
+
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        vec![Arc::new(CuratorsOnly)]
+    }
+
+    fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
+        ctx.kind(CommandType::ChatInput)
+            .description("Edit a server")
+            .set_options(EditOptions::create_options())
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        let locale = Locale::of(interaction);
+        let options = match EditOptions::from_interaction(interaction) {
+            Ok(options) => options,
+            Err(err) => return err.create_embed(locale).into(),
+        };
+        let input = PatchInput {
+            name: options.name.as_deref(),
+            icon_url: options.icon_url,
+            invite_url: options.invite_url,
+            description: options.description.as_deref(),
+            language: options.language.as_deref(),
+            tags: options.tags.as_deref(),
+            nsfw: options.nsfw,
+            banner_url: options.banner_url,
+            clear_icon: options.clear.as_deref() == Some("icon"),
+            clear_invite: options.clear.as_deref() == Some("invite"),
+            clear_description: options.clear.as_deref() == Some("description"),
+            clear_language: options.clear.as_deref() == Some("language"),
+            clear_tags: options.clear.as_deref() == Some("tags"),
+            clear_banner: options.clear.as_deref() == Some("banner"),
+        };
+        let patch = match input.validate() {
+            Ok(patch) => patch,
+            Err(err) => return err.create_embed(locale).into(),
+        };
+
+        let embed = if let Some((_, new_entry)) =
+            handler.database.update(options.guild_id, &patch).unwrap()
+        {
+            create_embed_from_entry(&new_entry, locale).title(tr!(locale, "edit.updated"))
+        } else {
+            CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "entries.not_found_guild"))
+        };
+        embed.into()
+    }
+}
@@ -1,61 +1,190 @@
+use std::sync::Arc;
+
 use serenity::{
     all::{
-        CommandInteraction, CommandOptionType, CommandType, Context, CreateCommand,
-        CreateCommandOption, CreateEmbed, ResolvedValue,
+        ButtonStyle, CommandInteraction, CommandType, ComponentInteraction, CreateActionRow,
+        CreateButton, CreateCommand, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage, Http, Permissions, Timestamp,
     },
     async_trait,
 };
 
-use crate::{Color, GuildlyHandler, commands::GuildlyCommand, create_embed_from_entry};
+use crate::{
+    Color, GuildEntry, GuildlyHandler, command_options,
+    commands::{CURATOR_PERMISSIONS, GuildlyCommand, GuildlyComponent},
+    create_embed_from_entry, custom_id, custom_id_args,
+    i18n::Locale,
+    middleware::{CuratorsOnly, Middleware},
+    options::{CommandOptions, OptionError},
+    response::{Reply, Response},
+    tr,
+};
+
+/// How long the Confirm button of `/remove` stays valid, in seconds.
+const CONFIRMATION_TIMEOUT: i64 = 60;
 
 pub struct RemoveServer;
 
+command_options! {
+    struct RemoveOptions {
+        guild_id: required u64 = "id", "Server Id";
+    }
+}
+
+impl RemoveServer {
+    fn find_entry(
+        handler: &GuildlyHandler,
+        interaction: &CommandInteraction,
+    ) -> Result<Option<GuildEntry>, OptionError> {
+        let options = RemoveOptions::from_interaction(interaction)?;
+        Ok(handler.database.get(options.guild_id).unwrap())
+    }
+
+    fn create_preview_embed(
+        found: &Result<Option<GuildEntry>, OptionError>,
+        locale: Locale,
+    ) -> CreateEmbed {
+        match found {
+            Ok(Some(entry)) => {
+                create_embed_from_entry(entry, locale).title(tr!(locale, "remove.preview"))
+            }
+            Ok(None) => CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "entries.not_found_guild")),
+            Err(err) => err.create_embed(locale),
+        }
+    }
+}
+
 #[async_trait]
 impl GuildlyCommand for RemoveServer {
     fn name(&self) -> &'static str {
         "remove"
     }
 
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        Some(CURATOR_PERMISSIONS)
+    }
+
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        vec![Arc::new(CuratorsOnly)]
+    }
+
     fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
         ctx.kind(CommandType::ChatInput)
             .description("Remove a server")
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "id", "Server Id")
-                    .required(true),
-            )
+            .set_options(RemoveOptions::create_options())
     }
 
     async fn execute(
         &self,
         handler: &GuildlyHandler,
-        _ctx: &Context,
+        _http: &Http,
         interaction: &CommandInteraction,
-    ) -> CreateEmbed {
-        let mut guild_id = None;
-        for option in interaction.data.options() {
-            if option.name == "id" {
-                let ResolvedValue::String(option) = option.value else {
-                    unreachable!()
-                };
-                let Ok(parsed) = option.parse::<u64>() else {
-                    return CreateEmbed::new()
-                        .color(Color::ERROR)
-                        .title("Invalid Server ID");
-                };
-                guild_id = Some(parsed)
-            }
+    ) -> Response {
+        let locale = Locale::of(interaction);
+        let found = Self::find_entry(handler, interaction);
+        let mut reply = Reply::new().embed(Self::create_preview_embed(&found, locale));
+
+        if let Ok(Some(entry)) = found {
+            let user_id = interaction.user.id;
+            reply = reply.components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(custom_id(
+                    "remove",
+                    format!("confirm:{}:{user_id}", entry.guild_id),
+                ))
+                .label(tr!(locale, "remove.confirm"))
+                .style(ButtonStyle::Danger),
+                CreateButton::new(custom_id(
+                    "remove",
+                    format!("cancel:{}:{user_id}", entry.guild_id),
+                ))
+                .label(tr!(locale, "remove.cancel"))
+                .style(ButtonStyle::Secondary),
+            ])]);
         }
 
-        let Some(guild_id) = guild_id else {
-            return CreateEmbed::new().color(Color::ERROR).title("No Guild ID");
+        reply.into()
+    }
+}
+
+/// Handles the Confirm/Cancel buttons attached by [`RemoveServer`].
+pub struct RemoveConfirmation;
+
+#[async_trait]
+impl GuildlyComponent for RemoveConfirmation {
+    fn prefix(&self) -> &'static str {
+        "remove"
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &ComponentInteraction,
+    ) -> CreateInteractionResponse {
+        let locale = Locale::of(interaction);
+        let mut parts = custom_id_args(&interaction.data.custom_id).split(':');
+        let (Some(action), Some(Ok(guild_id)), Some(Ok(user_id))) = (
+            parts.next(),
+            parts.next().map(str::parse::<u64>),
+            parts.next().map(str::parse::<u64>),
+        ) else {
+            return update_message(
+                CreateEmbed::new()
+                    .color(Color::ERROR)
+                    .title(tr!(locale, "remove.invalid")),
+            );
         };
 
+        if interaction.user.id.get() != user_id {
+            return CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .add_embed(
+                        CreateEmbed::new()
+                            .color(Color::WARN)
+                            .title(tr!(locale, "remove.not_owner")),
+                    )
+                    .ephemeral(true),
+            );
+        }
+
+        if Timestamp::now().unix_timestamp() - interaction.message.timestamp.unix_timestamp()
+            > CONFIRMATION_TIMEOUT
+        {
+            return update_message(
+                CreateEmbed::new()
+                    .color(Color::WARN)
+                    .title(tr!(locale, "remove.expired")),
+            );
+        }
+
+        if action != "confirm" {
+            return update_message(
+                CreateEmbed::new()
+                    .color(Color::INFO)
+                    .title(tr!(locale, "remove.cancelled")),
+            );
+        }
+
         if let Some(old_entry) = handler.database.remove(guild_id).unwrap() {
-            create_embed_from_entry(&old_entry).title("Removed Server")
+            update_message(
+                create_embed_from_entry(&old_entry, locale).title(tr!(locale, "remove.removed")),
+            )
         } else {
-            CreateEmbed::new()
-                .color(Color::ERROR)
-                .title("Not Found Guild")
+            update_message(
+                CreateEmbed::new()
+                    .color(Color::ERROR)
+                    .title(tr!(locale, "entries.not_found_guild")),
+            )
         }
     }
 }
+
+fn update_message(embed: CreateEmbed) -> CreateInteractionResponse {
+    CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(Vec::new()),
+    )
+}
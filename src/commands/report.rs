@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use serenity::{
+    all::{
+        ActionRowComponent, ButtonStyle, ComponentInteraction, CreateActionRow, CreateButton,
+        CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage,
+        CreateMessage, CreateModal, Http, InputTextStyle, ModalInteraction,
+    },
+    async_trait,
+};
+
+use crate::{
+    Color, DiscordLink, GuildEntry, GuildlyHandler,
+    commands::{GuildlyComponent, GuildlyModal},
+    create_embed_from_entry, custom_id, custom_id_args,
+    i18n::Locale,
+    rate_limit::{RateLimit, RateLimits},
+    tr,
+};
+
+/// Number of distinct users reporting an invite before curators are notified.
+const REPORT_THRESHOLD: u64 = 3;
+
+/// Discord allows at most 5 rows of 5 buttons per message.
+const MAX_BUTTONS: usize = 25;
+
+/// Creates a "Report" button for every entry with an invite.
+pub fn create_report_buttons(entries: &[GuildEntry], locale: Locale) -> Vec<CreateActionRow> {
+    let buttons = entries
+        .iter()
+        .filter(|entry| entry.invite_url.is_some())
+        .take(MAX_BUTTONS)
+        .map(|entry| {
+            let label = if entries.len() == 1 {
+                tr!(locale, "report.button").to_string()
+            } else {
+                let name = entry.name.chars().take(70).collect::<String>();
+                tr!(locale, "report.button_named", name = name)
+            };
+            CreateButton::new(custom_id("report", entry.guild_id))
+                .label(label)
+                .style(ButtonStyle::Secondary)
+        })
+        .collect::<Vec<_>>();
+
+    buttons
+        .chunks(5)
+        .map(|row| CreateActionRow::Buttons(row.to_vec()))
+        .collect()
+}
+
+fn parse_guild_id(custom_id: &str) -> Option<u64> {
+    custom_id_args(custom_id).parse().ok()
+}
+
+/// Checks whether the invite of `entry` still leads to its server.
+async fn validate_invite(http: &Http, entry: &GuildEntry) -> bool {
+    let Some(DiscordLink::Invite { code }) = entry
+        .invite_url
+        .as_ref()
+        .and_then(|url| DiscordLink::parse(url.as_str()))
+    else {
+        return false;
+    };
+
+    match http.get_invite(&code, false, false, None).await {
+        Ok(invite) => invite
+            .guild
+            .is_some_and(|guild| guild.id.get() == entry.guild_id),
+        Err(_) => false,
+    }
+}
+
+/// Opens the report form when a Report button is clicked.
+pub struct ReportButton;
+
+#[async_trait]
+impl GuildlyComponent for ReportButton {
+    fn prefix(&self) -> &'static str {
+        "report"
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &ComponentInteraction,
+    ) -> CreateInteractionResponse {
+        let locale = Locale::of(interaction);
+        let entry = parse_guild_id(&interaction.data.custom_id)
+            .and_then(|guild_id| handler.database.get(guild_id).unwrap());
+        let Some(entry) = entry else {
+            return create_not_found_response(locale);
+        };
+
+        let name = entry.name.chars().take(38).collect::<String>();
+        let title = tr!(locale, "report.form_title", name = name);
+        CreateInteractionResponse::Modal(
+            CreateModal::new(custom_id("report", entry.guild_id), title).components(vec![
+                CreateActionRow::InputText(
+                    CreateInputText::new(
+                        InputTextStyle::Paragraph,
+                        tr!(locale, "report.reason"),
+                        "reason",
+                    )
+                    .placeholder(tr!(locale, "report.reason_placeholder"))
+                    .required(false),
+                ),
+            ]),
+        )
+    }
+}
+
+/// Records submitted reports, re-validates the invite and notifies curators once an entry has
+/// been reported by three different users.
+pub struct ReportModal;
+
+#[async_trait]
+impl GuildlyModal for ReportModal {
+    fn prefix(&self) -> &'static str {
+        "report"
+    }
+
+    fn rate_limits(&self) -> RateLimits {
+        // Each report checks the invite with Discord.
+        RateLimits {
+            per_user: Some(RateLimit::new(2, Duration::from_secs(30))),
+            ..RateLimits::default()
+        }
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &ModalInteraction,
+    ) -> CreateInteractionResponse {
+        let locale = Locale::of(interaction);
+        let entry = parse_guild_id(&interaction.data.custom_id)
+            .and_then(|guild_id| handler.database.get(guild_id).unwrap());
+        let Some(entry) = entry else {
+            return create_not_found_response(locale);
+        };
+
+        let reason = interaction
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(text) if text.custom_id == "reason" => {
+                    text.value.clone()
+                }
+                _ => None,
+            })
+            .filter(|reason| !reason.trim().is_empty());
+
+        let invite_valid = validate_invite(http, &entry).await;
+        let reports = handler
+            .database
+            .insert_report(
+                entry.guild_id,
+                interaction.user.id.get(),
+                reason.as_deref(),
+                invite_valid,
+            )
+            .unwrap();
+
+        // Repeated reports by the same user don't count, so curators are alerted once per
+        // invite, by the report that reaches the threshold.
+        if reports == Some(REPORT_THRESHOLD)
+            && let Some(maintainer_channel) = handler.maintainer_channel
+        {
+            // Curators share the maintainer channel, so alerts use the default locale.
+            let curators = Locale::default();
+            let mut embed = create_embed_from_entry(&entry, curators)
+                .color(Color::WARN)
+                .title(tr!(curators, "report.alert", reports = REPORT_THRESHOLD))
+                .field(
+                    tr!(curators, "report.check"),
+                    if invite_valid {
+                        tr!(curators, "report.check_valid")
+                    } else {
+                        tr!(curators, "report.check_broken")
+                    },
+                    true,
+                );
+            if let Some(reason) = &reason {
+                embed = embed.field(tr!(curators, "report.latest_reason"), reason, false);
+            }
+            if let Err(why) = maintainer_channel
+                .send_message(http, CreateMessage::new().embed(embed))
+                .await
+            {
+                tracing::warn!(
+                    "Failed to notify curators about {}: {why:?}",
+                    entry.guild_id
+                );
+            }
+        }
+
+        let embed = if invite_valid {
+            CreateEmbed::new()
+                .color(Color::INFO)
+                .title(tr!(locale, "report.thanks"))
+                .description(tr!(locale, "report.still_valid"))
+        } else {
+            CreateEmbed::new()
+                .color(Color::WARN)
+                .title(tr!(locale, "report.thanks"))
+                .description(tr!(locale, "report.broken"))
+        };
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .add_embed(embed)
+                .ephemeral(true),
+        )
+    }
+}
+
+fn create_not_found_response(locale: Locale) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .add_embed(
+                CreateEmbed::new()
+                    .color(Color::ERROR)
+                    .title(tr!(locale, "entries.not_found_guild")),
+            )
+            .ephemeral(true),
+    )
+}
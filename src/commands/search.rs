@@ -1,18 +1,27 @@
 use serenity::{
-    all::{
-        CommandInteraction, CommandOptionType, CommandType, Context, CreateCommand,
-        CreateCommandOption, CreateEmbed, ResolvedValue,
-    },
+    all::{CommandInteraction, CommandType, CreateCommand, CreateEmbed, Http},
     async_trait,
 };
 
 use crate::{
-    Color, GuildlyHandler, commands::GuildlyCommand, create_embed_from_entries,
-    create_embed_from_entry,
+    Color, GuildEntry, GuildlyHandler, command_options,
+    commands::{GuildlyCommand, report::create_report_buttons},
+    create_embed_from_entries, create_embed_from_entry,
+    i18n::Locale,
+    options::CommandOptions,
+    response::{self, Reply, Response},
+    tr,
 };
 
 pub struct SearchServer;
 
+command_options! {
+    struct SearchOptions {
+        guild_id: optional u64 = "id", "Server Id";
+        name: optional String = "name", "Server Name";
+    }
+}
+
 #[async_trait]
 impl GuildlyCommand for SearchServer {
     fn name(&self) -> &'static str {
@@ -22,61 +31,60 @@ impl GuildlyCommand for SearchServer {
     fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
         ctx.kind(CommandType::ChatInput)
             .description("Search servers")
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "id", "Server Id")
-                    .required(false),
-            )
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "name", "Server Name")
-                    .required(false),
-            )
+            .set_options(SearchOptions::create_options())
+    }
+
+    fn user_installable(&self) -> bool {
+        true
     }
 
     async fn execute(
         &self,
         handler: &GuildlyHandler,
-        _ctx: &Context,
+        _http: &Http,
         interaction: &CommandInteraction,
-    ) -> CreateEmbed {
-        let mut guild_id = None;
-        let mut guild_name = None;
-        for option in interaction.data.options() {
-            match option.name {
-                "id" => {
-                    let ResolvedValue::String(option) = option.value else {
-                        unreachable!()
-                    };
-                    let Ok(parsed) = option.parse::<u64>() else {
-                        return CreateEmbed::new()
-                            .color(Color::ERROR)
-                            .title("Invalid Server ID");
-                    };
-                    guild_id = Some(parsed)
-                }
-                "name" => {
-                    let ResolvedValue::String(option) = option.value else {
-                        unreachable!()
-                    };
-                    guild_name = Some(option)
-                }
-                _ => {}
-            }
-        }
-        match (guild_id, guild_name) {
+    ) -> Response {
+        let (embed, entries) = Self::lookup(handler, interaction);
+        Reply::new()
+            .embed(embed)
+            .components(create_report_buttons(&entries, Locale::of(interaction)))
+            .into()
+    }
+}
+
+impl SearchServer {
+    /// Returns the response embed and the entries shown in it.
+    fn lookup(
+        handler: &GuildlyHandler,
+        interaction: &CommandInteraction,
+    ) -> (CreateEmbed, Vec<GuildEntry>) {
+        let locale = Locale::of(interaction);
+        let options = match SearchOptions::from_interaction(interaction) {
+            Ok(options) => options,
+            Err(err) => return (err.create_embed(locale), Vec::new()),
+        };
+        match (options.guild_id, options.name) {
             (Some(guild_id), None) => {
                 if let Some(entry) = handler.database.get(guild_id).unwrap() {
-                    create_embed_from_entry(&entry)
+                    (create_embed_from_entry(&entry, locale), vec![entry])
                 } else {
-                    CreateEmbed::new()
-                        .color(Color::WARN)
-                        .title("No Servers Found")
+                    // Like the show menu, only searches in servers the bot is in are recorded.
+                    if interaction.guild_id.is_some() && !response::bot_is_absent(interaction) {
+                        handler.database.record_miss(guild_id, None).unwrap();
+                    }
+                    (create_embed_from_entries(&[], locale), Vec::new())
                 }
             }
             (None, Some(guild_name)) => {
-                let entries = handler.database.search(guild_name).unwrap();
-                create_embed_from_entries(&entries)
+                let entries = handler.database.search(&guild_name).unwrap();
+                (create_embed_from_entries(&entries, locale), entries)
             }
-            _ => todo!(),
+            _ => (
+                CreateEmbed::new()
+                    .color(Color::ERROR)
+                    .title(tr!(locale, "search.either")),
+                Vec::new(),
+            ),
         }
     }
 }
@@ -1,12 +1,80 @@
+use std::{collections::HashSet, time::Duration};
+
 use serenity::{
-    all::{CommandInteraction, CommandType, Context, CreateCommand, CreateEmbed},
+    all::{
+        CommandInteraction, CommandType, CreateActionRow, CreateCommand, CreateEmbed,
+        CreateEmbedFooter, GuildId, Http, Message,
+    },
     async_trait,
 };
 
-use crate::{GuildlyHandler, commands::GuildlyCommand, create_embed_from_entries};
+use crate::{
+    Color, GuildEntry, GuildlyHandler,
+    commands::{GuildlyCommand, report::create_report_buttons},
+    create_embed_from_entries, create_embed_from_entry, create_entry_link,
+    i18n::Locale,
+    link::find_guild_ids,
+    rate_limit::{RateLimit, RateLimits},
+    response::{self, Reply, Response},
+    source::{LinkSource, message_texts},
+    tr,
+};
+
+/// How many unlisted guild IDs are shown before the list is cut off.
+const MAX_UNLISTED: usize = 10;
 
 pub struct ShowServersMenu;
 
+fn create_embed_from_found(
+    found: &[(GuildEntry, LinkSource)],
+    unlisted: &[(u64, LinkSource)],
+    locale: Locale,
+) -> CreateEmbed {
+    let found_in =
+        |source: &LinkSource| tr!(locale, "show.found_in", source = source.describe(locale));
+    let embed = match found {
+        [] => create_embed_from_entries(&[], locale),
+        [(entry, source)] => {
+            create_embed_from_entry(entry, locale).footer(CreateEmbedFooter::new(found_in(source)))
+        }
+        _ => CreateEmbed::new()
+            .color(Color::INFO)
+            .title(tr!(locale, "entries.servers"))
+            .fields(
+                found
+                    .iter()
+                    .map(|(entry, source)| (create_entry_link(entry), found_in(source), false)),
+            ),
+    };
+
+    if unlisted.is_empty() {
+        return embed;
+    }
+
+    let mut lines = unlisted
+        .iter()
+        .take(MAX_UNLISTED)
+        .map(|(guild_id, source)| {
+            let created_at = GuildId::new(*guild_id).created_at().unix_timestamp();
+            tr!(
+                locale,
+                "show.unlisted_entry",
+                guild_id = guild_id,
+                created_at = created_at,
+                source = source.describe(locale),
+            )
+        })
+        .collect::<Vec<_>>();
+    if unlisted.len() > MAX_UNLISTED {
+        lines.push(tr!(
+            locale,
+            "show.more",
+            count = unlisted.len() - MAX_UNLISTED
+        ));
+    }
+    embed.field(tr!(locale, "show.unlisted"), lines.join("\n"), false)
+}
+
 #[async_trait]
 impl GuildlyCommand for ShowServersMenu {
     fn name(&self) -> &'static str {
@@ -17,20 +85,93 @@ impl GuildlyCommand for ShowServersMenu {
         ctx.kind(CommandType::Message)
     }
 
+    fn slow(&self) -> bool {
+        // Attachments are downloaded and forwarded messages fetched one by one.
+        true
+    }
+
+    fn user_installable(&self) -> bool {
+        true
+    }
+
+    fn rate_limits(&self) -> RateLimits {
+        // Each use may download attachments and fetch forwarded messages.
+        RateLimits {
+            per_user: Some(RateLimit::new(3, Duration::from_secs(5))),
+            ..RateLimits::default()
+        }
+    }
+
     async fn execute(
         &self,
         handler: &GuildlyHandler,
-        _ctx: &Context,
+        http: &Http,
         interaction: &CommandInteraction,
-    ) -> CreateEmbed {
-        let mut entries = Vec::new();
-        for message in interaction.data.resolved.messages.values() {
-            for guild_id in handler.guild_link_finder(&message.content) {
-                if let Some(result) = handler.database.get(guild_id).unwrap() {
-                    entries.push(result);
+    ) -> Response {
+        let messages = interaction
+            .data
+            .resolved
+            .messages
+            .values()
+            .collect::<Vec<_>>();
+        let http = (!response::bot_is_absent(interaction)).then_some(http);
+        let (found, unlisted) =
+            lookup_messages(handler, http, &messages, interaction.guild_id).await;
+        let (embed, components) = create_lookup_reply(found, &unlisted, Locale::of(interaction));
+        Reply::new().embed(embed).components(components).into()
+    }
+}
+
+/// Resolves the links of `messages` into listed entries and unlisted guild IDs, each with the
+/// part of the message it was found in. Lookups of unlisted guilds are recorded in servers. Without
+/// `http`, as when the bot cannot see the channel, forwarded messages are not fetched and
+/// nothing is recorded.
+pub(crate) async fn lookup_messages(
+    handler: &GuildlyHandler,
+    http: Option<&Http>,
+    messages: &[&Message],
+    guild_id: Option<GuildId>,
+) -> (Vec<(GuildEntry, LinkSource)>, Vec<(u64, LinkSource)>) {
+    // `/wanted` shows curators where misses were looked up, so only servers the bot is in are
+    // recorded, not DMs or channels it was not invited to.
+    let record_misses = guild_id.is_some() && http.is_some();
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    let mut unlisted = Vec::new();
+    for message in messages {
+        for (source, text) in message_texts(http, message).await {
+            for linked_guild_id in find_guild_ids(&text) {
+                if !seen.insert(linked_guild_id) {
+                    continue;
+                }
+                if let Some(result) = handler.database.get(linked_guild_id).unwrap() {
+                    found.push((result, source.clone()));
+                } else {
+                    if record_misses {
+                        let link = message.id.link(message.channel_id, guild_id);
+                        handler
+                            .database
+                            .record_miss(linked_guild_id, Some(&link))
+                            .unwrap();
+                    }
+                    unlisted.push((linked_guild_id, source.clone()));
                 }
             }
         }
-        create_embed_from_entries(&entries)
     }
+    (found, unlisted)
+}
+
+/// Creates the embed listing a lookup, and report buttons for its entries.
+pub(crate) fn create_lookup_reply(
+    found: Vec<(GuildEntry, LinkSource)>,
+    unlisted: &[(u64, LinkSource)],
+    locale: Locale,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let embed = create_embed_from_found(&found, unlisted, locale);
+    let entries = found
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .collect::<Vec<_>>();
+    (embed, create_report_buttons(&entries, locale))
 }
@@ -0,0 +1,266 @@
+use serenity::{
+    all::{
+        ButtonStyle, CommandInteraction, CommandType, ComponentInteraction, CreateActionRow,
+        CreateButton, CreateCommand, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseMessage, CreateMessage, Http, UserId,
+    },
+    async_trait,
+};
+use url::Url;
+
+use crate::{
+    Color, GuildlyHandler, Suggestion, SuggestionStatus, command_options,
+    commands::{GuildlyCommand, GuildlyComponent},
+    create_embed_from_entry, custom_id, custom_id_args,
+    i18n::Locale,
+    options::CommandOptions,
+    response::{Reply, Response},
+    tr,
+    validation::EntryInput,
+};
+
+pub struct SuggestServer;
+
+command_options! {
+    struct SuggestOptions {
+        guild_id: required u64 = "id", "Server Id";
+        name: required String = "name", "Guild Name";
+        invite_url: optional Url = "invite", "Invite Url";
+        icon_url: optional Url = "icon", "Icon Url";
+        description: optional String = "description", "Description";
+        language: optional String = "language", "Main language, e.g. en or ja";
+        tags: optional String = "tags", "Comma separated tags";
+        nsfw: optional bool = "nsfw", "Whether the server is age-restricted";
+        banner_url: optional Url = "banner", "Banner Url";
+    }
+}
+
+/// Creates the embed posted for review. Curators share the maintainer channel, so it and the
+/// submitter's DM use the default locale.
+fn create_embed_from_suggestion(suggestion: &Suggestion) -> CreateEmbed {
+    create_embed_from_entry(&suggestion.entry, Locale::default()).field(
+        tr!(Locale::default(), "suggest.suggested_by"),
+        format!("<@{}>", suggestion.submitter_id),
+        true,
+    )
+}
+
+#[async_trait]
+impl GuildlyCommand for SuggestServer {
+    fn name(&self) -> &'static str {
+        "suggest"
+    }
+
+    fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
+        ctx.kind(CommandType::ChatInput)
+            .description("Suggest a server for the directory")
+            .set_options(SuggestOptions::create_options())
+    }
+
+    fn ephemeral(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        let embed = Self::submit(handler, http, interaction).await;
+        Reply::new().embed(embed).ephemeral(true).into()
+    }
+}
+
+impl SuggestServer {
+    async fn submit(
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+    ) -> CreateEmbed {
+        let locale = Locale::of(interaction);
+        let Some(maintainer_channel) = handler.maintainer_channel else {
+            return CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "suggest.disabled"));
+        };
+
+        let options = match SuggestOptions::from_interaction(interaction) {
+            Ok(options) => options,
+            Err(err) => return err.create_embed(locale),
+        };
+        let input = EntryInput {
+            guild_id: Some(options.guild_id),
+            name: Some(&options.name),
+            icon_url: options.icon_url,
+            invite_url: options.invite_url,
+            description: options.description.as_deref(),
+            language: options.language.as_deref(),
+            tags: options.tags.as_deref(),
+            nsfw: options.nsfw.unwrap_or(false),
+            banner_url: options.banner_url,
+        };
+        let entry = match input.validate() {
+            Ok(entry) => entry,
+            Err(err) => return err.create_embed(locale),
+        };
+
+        if let Some(existing) = handler.database.get(entry.guild_id).unwrap() {
+            return create_embed_from_entry(&existing, locale)
+                .color(Color::WARN)
+                .title(tr!(locale, "suggest.already_listed"));
+        }
+
+        let id = handler
+            .database
+            .insert_suggestion(&entry, interaction.user.id.get())
+            .unwrap();
+        let suggestion = handler.database.get_suggestion(id).unwrap().unwrap();
+
+        let curators = Locale::default();
+        let review = CreateMessage::new()
+            .embed(
+                create_embed_from_suggestion(&suggestion)
+                    .title(tr!(curators, "suggest.new"))
+                    .footer(CreateEmbedFooter::new(tr!(
+                        curators,
+                        "suggest.number",
+                        id = id
+                    ))),
+            )
+            .components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(custom_id("suggestion", format!("approve:{id}")))
+                    .label(tr!(curators, "suggest.approve"))
+                    .style(ButtonStyle::Success),
+                CreateButton::new(custom_id("suggestion", format!("reject:{id}")))
+                    .label(tr!(curators, "suggest.reject"))
+                    .style(ButtonStyle::Danger),
+            ])]);
+        if let Err(why) = maintainer_channel.send_message(http, review).await {
+            // Nobody could review it, so don't keep it around as pending.
+            tracing::error!("Failed to post suggestion #{id}: {why:?}");
+            handler.database.delete_suggestion(id).unwrap();
+            return CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "suggest.failed"));
+        }
+
+        create_embed_from_entry(&entry, locale)
+            .title(tr!(locale, "suggest.submitted"))
+            .description(tr!(locale, "suggest.submitted_hint"))
+    }
+}
+
+/// Handles the Approve/Reject buttons of suggestions posted in the maintainer channel. Only
+/// [curators](GuildlyHandler::is_curator) can review suggestions.
+pub struct SuggestionReview;
+
+#[async_trait]
+impl GuildlyComponent for SuggestionReview {
+    fn prefix(&self) -> &'static str {
+        "suggestion"
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &ComponentInteraction,
+    ) -> CreateInteractionResponse {
+        let locale = Locale::of(interaction);
+        if handler.maintainer_channel != Some(interaction.channel_id) {
+            return ephemeral_message(tr!(locale, "suggest.invalid"));
+        }
+        let member = interaction.member.as_ref();
+        if !handler.is_curator(http, interaction.guild_id, member).await {
+            return ephemeral_message(tr!(locale, "suggest.not_curator"));
+        }
+
+        let mut parts = custom_id_args(&interaction.data.custom_id).split(':');
+        let status = match parts.next() {
+            Some("approve") => SuggestionStatus::Approved,
+            Some("reject") => SuggestionStatus::Rejected,
+            _ => return ephemeral_message(tr!(locale, "suggest.invalid")),
+        };
+        let Some(Ok(id)) = parts.next().map(str::parse::<u64>) else {
+            return ephemeral_message(tr!(locale, "suggest.invalid"));
+        };
+
+        // Approving must not overwrite a server that was listed since it was suggested.
+        if status == SuggestionStatus::Approved {
+            let listed = match handler.database.get_suggestion(id).unwrap() {
+                Some(suggestion) => handler.database.get(suggestion.entry.guild_id).unwrap(),
+                None => None,
+            };
+            if listed.is_some() {
+                return ephemeral_message(tr!(locale, "suggest.listed_since"));
+            }
+        }
+
+        let reviewer_id = interaction.user.id;
+        let Some(suggestion) = handler
+            .database
+            .decide_suggestion(id, status, reviewer_id.get())
+            .unwrap()
+        else {
+            return ephemeral_message(tr!(locale, "suggest.already_reviewed"));
+        };
+
+        let curators = Locale::default();
+        let (title, color, notice) = match status {
+            SuggestionStatus::Approved => {
+                handler.database.insert(&suggestion.entry).unwrap();
+                (
+                    tr!(curators, "suggest.approved"),
+                    Color::INFO,
+                    tr!(curators, "suggest.approved_notice"),
+                )
+            }
+            _ => (
+                tr!(curators, "suggest.rejected"),
+                Color::WARN,
+                tr!(curators, "suggest.rejected_notice"),
+            ),
+        };
+
+        let notification = CreateMessage::new().embed(
+            create_embed_from_entry(&suggestion.entry, curators)
+                .color(color)
+                .title(notice),
+        );
+        if let Err(why) = UserId::new(suggestion.submitter_id)
+            .direct_message(http, notification)
+            .await
+        {
+            tracing::warn!("Failed to notify the submitter of suggestion #{id}: {why:?}");
+        }
+
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .embed(
+                    create_embed_from_suggestion(&suggestion)
+                        .color(color)
+                        .title(title)
+                        .field(
+                            tr!(curators, "suggest.reviewed_by"),
+                            format!("<@{reviewer_id}>"),
+                            true,
+                        )
+                        .footer(CreateEmbedFooter::new(tr!(
+                            curators,
+                            "suggest.number",
+                            id = id
+                        ))),
+                )
+                .components(Vec::new()),
+        )
+    }
+}
+
+fn ephemeral_message(title: &str) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .add_embed(CreateEmbed::new().color(Color::WARN).title(title))
+            .ephemeral(true),
+    )
+}
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use serenity::{
+    all::{
+        CommandInteraction, CommandOptionType, CommandType, CreateCommand, CreateCommandOption,
+        CreateEmbed, Http, Permissions, ResolvedOption, ResolvedValue,
+    },
+    async_trait,
+};
+
+use crate::{
+    Color, GuildPatch, GuildlyHandler, command_options,
+    commands::{CURATOR_PERMISSIONS, GuildlyCommand},
+    create_embed_from_entry,
+    i18n::{self, Locale},
+    middleware::{CuratorsOnly, Middleware},
+    options::CommandOptions,
+    response::Response,
+    tr,
+    validation::{ValidationError, parse_tags},
+};
+
+/// Embed descriptions hold at most 4096 characters.
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+pub struct TagServer;
+
+command_options! {
+    struct TagOptions {
+        guild_id: required u64 = "id", "Server Id";
+        tags: required String = "tags", "Comma separated tags";
+    }
+}
+
+/// Creates the subcommand `/tag <name>`, translated under `options.tag_<name>`.
+fn create_subcommand(
+    name: &'static str,
+    description: &'static str,
+    options: Vec<CreateCommandOption>,
+) -> CreateCommandOption {
+    let subcommand = options.into_iter().fold(
+        CreateCommandOption::new(CommandOptionType::SubCommand, name, description),
+        CreateCommandOption::add_sub_option,
+    );
+    i18n::localize_option(subcommand, &format!("tag_{name}"))
+}
+
+#[async_trait]
+impl GuildlyCommand for TagServer {
+    fn name(&self) -> &'static str {
+        "tag"
+    }
+
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        Some(CURATOR_PERMISSIONS)
+    }
+
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        vec![Arc::new(CuratorsOnly)]
+    }
+
+    fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
+        ctx.kind(CommandType::ChatInput)
+            .description("Sort servers into the categories of /browse")
+            .add_option(create_subcommand(
+                "add",
+                "Add tags to a server",
+                TagOptions::create_options(),
+            ))
+            .add_option(create_subcommand(
+                "remove",
+                "Remove tags from a server",
+                TagOptions::create_options(),
+            ))
+            .add_option(create_subcommand(
+                "list",
+                "List the tags in use",
+                Vec::new(),
+            ))
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        let locale = Locale::of(interaction);
+        let options = interaction.data.options();
+        let Some(ResolvedOption {
+            name,
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) = options.first()
+        else {
+            return CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "tag.invalid"))
+                .into();
+        };
+
+        match *name {
+            "add" => Self::retag(handler, options, true, locale),
+            "remove" => Self::retag(handler, options, false, locale),
+            _ => Self::list(handler, locale),
+        }
+        .into()
+    }
+}
+
+impl TagServer {
+    /// Adds the given tags to an entry, or removes them if `add` is false.
+    fn retag(
+        handler: &GuildlyHandler,
+        options: &[ResolvedOption],
+        add: bool,
+        locale: Locale,
+    ) -> CreateEmbed {
+        let options = match TagOptions::parse(options) {
+            Ok(options) => options,
+            Err(err) => return err.create_embed(locale),
+        };
+        let changed = match parse_tags(&options.tags) {
+            Ok(tags) => tags,
+            Err(err) => return err.create_embed(locale),
+        };
+        let Some(entry) = handler.database.get(options.guild_id).unwrap() else {
+            return CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "entries.not_found_guild"));
+        };
+
+        let mut tags = entry.tags.clone();
+        if add {
+            tags.extend(changed);
+            tags.sort();
+            tags.dedup();
+        } else {
+            tags.retain(|tag| !changed.contains(tag));
+        }
+        if tags == entry.tags {
+            return ValidationError::NoChanges.create_embed(locale);
+        }
+
+        let patch = GuildPatch {
+            tags: Some(tags),
+            ..GuildPatch::default()
+        };
+        // The entry may have been removed since it was read.
+        let Some((_, new_entry)) = handler.database.update(options.guild_id, &patch).unwrap()
+        else {
+            return CreateEmbed::new()
+                .color(Color::ERROR)
+                .title(tr!(locale, "entries.not_found_guild"));
+        };
+        create_embed_from_entry(&new_entry, locale).title(tr!(locale, "tag.updated"))
+    }
+
+    fn list(handler: &GuildlyHandler, locale: Locale) -> CreateEmbed {
+        let tags = handler.database.tags().unwrap();
+        if tags.is_empty() {
+            return CreateEmbed::new()
+                .color(Color::INFO)
+                .title(tr!(locale, "tag.none"));
+        }
+
+        // Leave room for the line counting the tags that did not fit.
+        let mut description = String::new();
+        let mut shown = 0;
+        for (tag, entries) in &tags {
+            let line = tr!(locale, "tag.entry", tag = tag, entries = entries);
+            if description.len() + line.len() + 1 > MAX_DESCRIPTION_LENGTH - 64 {
+                break;
+            }
+            description.push_str(&line);
+            description.push('\n');
+            shown += 1;
+        }
+        if shown < tags.len() {
+            description.push_str(&tr!(locale, "tag.more", count = tags.len() - shown));
+        }
+        CreateEmbed::new()
+            .color(Color::INFO)
+            .title(tr!(locale, "tag.title"))
+            .description(description.trim_end())
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use serenity::{
+    all::{CommandInteraction, CommandType, CreateCommand, CreateEmbed, Http, Permissions},
+    async_trait,
+};
+
+use crate::{
+    Color, GuildlyHandler, command_options,
+    commands::{CURATOR_PERMISSIONS, GuildlyCommand},
+    i18n::Locale,
+    middleware::{CuratorsOnly, Middleware},
+    options::CommandOptions,
+    response::{Reply, Response},
+    tr,
+};
+
+const DEFAULT_LIMIT: i64 = 10;
+/// Embeds hold at most 25 fields.
+const MAX_LIMIT: i64 = 25;
+/// Fields hold at most 1024 characters, so only the latest message links are shown.
+const MAX_LINKS: usize = 5;
+
+pub struct WantedServers;
+
+command_options! {
+    struct WantedOptions {
+        limit: optional i64 = "limit", "Number of servers to list", |option| {
+            option.min_int_value(1).max_int_value(MAX_LIMIT as u64)
+        };
+    }
+}
+
+#[async_trait]
+impl GuildlyCommand for WantedServers {
+    fn name(&self) -> &'static str {
+        "wanted"
+    }
+
+    fn default_member_permissions(&self) -> Option<Permissions> {
+        Some(CURATOR_PERMISSIONS)
+    }
+
+    fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        vec![Arc::new(CuratorsOnly)]
+    }
+
+    fn ephemeral(&self) -> bool {
+        true
+    }
+
+    fn create_command(&self, ctx: CreateCommand) -> CreateCommand {
+        ctx.kind(CommandType::ChatInput)
+            .description("List the most looked up servers that are not in the directory")
+            .set_options(WantedOptions::create_options())
+    }
+
+    async fn execute(
+        &self,
+        handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        // Message links reveal where other people posted, so only the curator sees them.
+        let embed = Self::list(handler, interaction);
+        Reply::new().embed(embed).ephemeral(true).into()
+    }
+}
+
+impl WantedServers {
+    fn list(handler: &GuildlyHandler, interaction: &CommandInteraction) -> CreateEmbed {
+        let locale = Locale::of(interaction);
+        let limit = match WantedOptions::from_interaction(interaction) {
+            Ok(options) => options.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+            Err(err) => return err.create_embed(locale),
+        };
+
+        let misses = handler.database.wanted(limit as usize).unwrap();
+        if misses.is_empty() {
+            return CreateEmbed::new()
+                .color(Color::INFO)
+                .title(tr!(locale, "wanted.none"));
+        }
+
+        CreateEmbed::new()
+            .color(Color::INFO)
+            .title(tr!(locale, "wanted.title"))
+            .fields(misses.iter().map(|miss| {
+                let mut value = tr!(locale, "wanted.last_seen", last_seen = miss.last_seen);
+                for message_link in miss.message_links.iter().take(MAX_LINKS) {
+                    value += &format!("\n{message_link}");
+                }
+                if miss.message_links.len() > MAX_LINKS {
+                    value += "\n";
+                    value += &tr!(
+                        locale,
+                        "wanted.more",
+                        count = miss.message_links.len() - MAX_LINKS
+                    );
+                }
+                (
+                    tr!(
+                        locale,
+                        "wanted.entry",
+                        guild_id = miss.guild_id,
+                        lookups = miss.lookups
+                    ),
+                    value,
+                    false,
+                )
+            }))
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::all::GatewayIntents;
+use tracing_subscriber::EnvFilter;
+
+use crate::commands::builtin_commands;
+
+/// Prefix of the environment variables read by [`Config::from_env`].
+pub const ENV_PREFIX: &str = "GUILDLY_";
+
+/// Config file read when neither `--config` nor `GUILDLY_CONFIG` is given, if it exists.
+pub const DEFAULT_CONFIG_FILE: &str = "guildly.toml";
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    InvalidEnv(String),
+    UnknownIntent(String),
+    UnknownCommand(String),
+    InvalidLogLevel(String),
+    MissingToken,
+    MissingDatabase,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(path, err) => write!(f, "cannot read {}: {err}", path.display()),
+            Self::Parse(path, err) => write!(f, "invalid config {}: {err}", path.display()),
+            Self::InvalidEnv(name) => write!(f, "invalid value of {name}"),
+            Self::UnknownIntent(name) => write!(f, "unknown intent `{name}`"),
+            Self::UnknownCommand(name) => write!(f, "unknown command `{name}`"),
+            Self::InvalidLogLevel(level) => write!(f, "invalid log level `{level}`"),
+            Self::MissingToken => f.write_str("no bot token, set `token` or GUILDLY_TOKEN"),
+            Self::MissingDatabase => {
+                f.write_str("no database, set `database`, GUILDLY_DATABASE or pass --database")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Settings of the bot. Every layer is partial: [`Config::merge`] lets a config file be
+/// overridden by `GUILDLY_*` environment variables, which are in turn overridden by the command
+/// line.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub token: Option<String>,
+    pub database: Option<PathBuf>,
+    /// Gateway intent names such as `GUILD_MESSAGES`.
+    pub intents: Option<Vec<String>>,
+    /// Names of the commands to register. All commands are registered when unset.
+    pub commands: Option<Vec<String>>,
+    /// A level or `tracing` filter directive such as `info,serenity=warn`.
+    pub log_level: Option<String>,
+    /// See [`GuildlyHandlerBuilder::maintainer_channel`](crate::builder::GuildlyHandlerBuilder::maintainer_channel).
+    pub maintainer_channel: Option<u64>,
+    /// See [`GuildlyHandlerBuilder::dev_guild`](crate::builder::GuildlyHandlerBuilder::dev_guild).
+    pub dev_guild: Option<u64>,
+}
+
+impl Config {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| ConfigError::Read(path.to_path_buf(), err))?;
+        toml::from_str(&text).map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+    }
+
+    /// Reads `GUILDLY_TOKEN`, `GUILDLY_DATABASE`, `GUILDLY_INTENTS`, `GUILDLY_COMMANDS`,
+    /// `GUILDLY_LOG_LEVEL`, `GUILDLY_MAINTAINER_CHANNEL` and `GUILDLY_DEV_GUILD`. Lists are comma
+    /// separated.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            token: env_var("TOKEN"),
+            database: env_var("DATABASE").map(PathBuf::from),
+            intents: env_var("INTENTS").map(|value| split_list(&value)),
+            commands: env_var("COMMANDS").map(|value| split_list(&value)),
+            log_level: env_var("LOG_LEVEL"),
+            maintainer_channel: env_id("MAINTAINER_CHANNEL")?,
+            dev_guild: env_id("DEV_GUILD")?,
+        })
+    }
+
+    /// Returns the config file named by `path`, `GUILDLY_CONFIG` or [`DEFAULT_CONFIG_FILE`],
+    /// layered under the environment.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env_var("CONFIG").map(PathBuf::from));
+        let file = match path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).is_file() => {
+                Self::from_file(DEFAULT_CONFIG_FILE)?
+            }
+            None => Self::default(),
+        };
+        Ok(file.merge(Self::from_env()?))
+    }
+
+    /// Layers `other` on top of `self`, keeping the values `other` leaves unset.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            token: other.token.or(self.token),
+            database: other.database.or(self.database),
+            intents: other.intents.or(self.intents),
+            commands: other.commands.or(self.commands),
+            log_level: other.log_level.or(self.log_level),
+            maintainer_channel: other.maintainer_channel.or(self.maintainer_channel),
+            dev_guild: other.dev_guild.or(self.dev_guild),
+        }
+    }
+
+    pub fn token(&self) -> Result<&str, ConfigError> {
+        self.token.as_deref().ok_or(ConfigError::MissingToken)
+    }
+
+    pub fn database(&self) -> Result<&Path, ConfigError> {
+        self.database.as_deref().ok_or(ConfigError::MissingDatabase)
+    }
+
+    pub fn intents(&self) -> Result<GatewayIntents, ConfigError> {
+        let Some(names) = &self.intents else {
+            return Ok(GatewayIntents::GUILD_MESSAGES
+                | GatewayIntents::DIRECT_MESSAGES
+                | GatewayIntents::MESSAGE_CONTENT);
+        };
+        names
+            .iter()
+            .try_fold(GatewayIntents::empty(), |intents, name| {
+                GatewayIntents::from_name(&name.trim().to_uppercase())
+                    .map(|intent| intents | intent)
+                    .ok_or_else(|| ConfigError::UnknownIntent(name.clone()))
+            })
+    }
+
+    pub fn log_filter(&self) -> Result<EnvFilter, ConfigError> {
+        let level = self.log_level.as_deref().unwrap_or(DEFAULT_LOG_LEVEL);
+        EnvFilter::try_new(level).map_err(|_| ConfigError::InvalidLogLevel(level.to_string()))
+    }
+
+    /// Whether the command called `name` should be registered.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.commands
+            .as_ref()
+            .is_none_or(|commands| commands.iter().any(|command| command == name))
+    }
+
+    /// Checks every setting needed to run the bot.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.database()?;
+        self.intents()?;
+        self.log_filter()?;
+        self.validate_registry()
+    }
+
+    /// Checks only the settings needed to manage the registered commands, which never touch the
+    /// database.
+    pub fn validate_registry(&self) -> Result<(), ConfigError> {
+        self.token()?;
+        let available = builtin_commands()
+            .iter()
+            .map(|command| command.name())
+            .collect::<Vec<_>>();
+        if let Some(unknown) = self
+            .commands
+            .iter()
+            .flatten()
+            .find(|name| !available.contains(&name.as_str()))
+        {
+            return Err(ConfigError::UnknownCommand(unknown.clone()));
+        }
+        Ok(())
+    }
+
+    /// Returns a copy that is safe to print, with the token hidden.
+    pub fn redacted(&self) -> Self {
+        Self {
+            token: self.token.as_ref().map(|_| REDACTED.to_string()),
+            ..self.clone()
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(format!("{ENV_PREFIX}{name}"))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+fn env_id(name: &str) -> Result<Option<u64>, ConfigError> {
+    env_var(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| ConfigError::InvalidEnv(format!("{ENV_PREFIX}{name}")))
+        })
+        .transpose()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
@@ -1,9 +1,18 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use duckdb::{Connection, Error, params};
+use duckdb::{Connection, Error, Row, params};
 use url::Url;
 
-use crate::GuildEntry;
+use crate::{GuildEntry, GuildPatch, LookupMiss, Suggestion, SuggestionStatus};
+
+/// The columns read by [`guild_from_row`], with the tags of a guild joined by commas.
+const GUILD_COLUMNS: &str = "name, guild_id, invite_url, icon_url, description,
+    language, nsfw, banner_url, added_at, updated_at,
+    (SELECT string_agg(tag, ',' ORDER BY tag) FROM guild_tags
+     WHERE guild_tags.guild_id = guilds.guild_id)";
 
 pub struct Database {
     database: Connection,
@@ -15,31 +24,148 @@ unsafe impl Sync for Database {}
 impl Database {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let conn = Connection::open(path)?;
+        // The checkpoint writes the schema to the database file, so that the WAL left behind
+        // by a process that did not close the database only has to replay data.
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS guilds (
                 guild_id   BIGINT PRIMARY KEY,
                 name       TEXT NOT NULL,
                 invite_url TEXT,
                 icon_url   TEXT
-            );",
+            );
+            ALTER TABLE guilds ADD COLUMN IF NOT EXISTS description TEXT;
+            ALTER TABLE guilds ADD COLUMN IF NOT EXISTS language TEXT;
+            ALTER TABLE guilds ADD COLUMN IF NOT EXISTS nsfw BOOLEAN DEFAULT false;
+            ALTER TABLE guilds ADD COLUMN IF NOT EXISTS banner_url TEXT;
+            ALTER TABLE guilds ADD COLUMN IF NOT EXISTS added_at BIGINT;
+            ALTER TABLE guilds ADD COLUMN IF NOT EXISTS updated_at BIGINT;
+            CREATE TABLE IF NOT EXISTS guild_tags (
+                guild_id BIGINT NOT NULL,
+                tag      TEXT NOT NULL,
+                PRIMARY KEY (guild_id, tag)
+            );
+            CREATE TABLE IF NOT EXISTS lookup_misses (
+                guild_id     BIGINT PRIMARY KEY,
+                lookups      BIGINT NOT NULL,
+                last_seen    BIGINT NOT NULL,
+                channel_id   BIGINT,
+                message_link TEXT
+            );
+            CREATE TABLE IF NOT EXISTS lookup_links (
+                guild_id     BIGINT NOT NULL,
+                message_link TEXT NOT NULL,
+                seen_at      BIGINT NOT NULL,
+                PRIMARY KEY (guild_id, message_link)
+            );
+            INSERT OR IGNORE INTO lookup_links
+                SELECT guild_id, message_link, last_seen FROM lookup_misses
+                WHERE message_link IS NOT NULL;
+            UPDATE lookup_misses SET channel_id = NULL, message_link = NULL
+                WHERE message_link IS NOT NULL;
+            CREATE TABLE IF NOT EXISTS suggestions (
+                id           BIGINT PRIMARY KEY,
+                guild_id     BIGINT NOT NULL,
+                name         TEXT NOT NULL,
+                invite_url   TEXT,
+                icon_url     TEXT,
+                description  TEXT,
+                submitter_id BIGINT NOT NULL,
+                status       TEXT NOT NULL,
+                reviewer_id  BIGINT,
+                created_at   BIGINT NOT NULL,
+                decided_at   BIGINT
+            );
+            ALTER TABLE suggestions ADD COLUMN IF NOT EXISTS language TEXT;
+            ALTER TABLE suggestions ADD COLUMN IF NOT EXISTS tags TEXT;
+            ALTER TABLE suggestions ADD COLUMN IF NOT EXISTS nsfw BOOLEAN DEFAULT false;
+            ALTER TABLE suggestions ADD COLUMN IF NOT EXISTS banner_url TEXT;
+            CREATE TABLE IF NOT EXISTS invite_reports (
+                id           BIGINT PRIMARY KEY,
+                guild_id     BIGINT NOT NULL,
+                reporter_id  BIGINT NOT NULL,
+                reason       TEXT,
+                invite_valid BOOLEAN NOT NULL,
+                created_at   BIGINT NOT NULL
+            );
+            DELETE FROM invite_reports WHERE id NOT IN (
+                SELECT MAX(id) FROM invite_reports GROUP BY guild_id, reporter_id
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS invite_reports_reporter
+                ON invite_reports (guild_id, reporter_id);
+            CHECKPOINT;",
         )?;
         Ok(Self { database: conn })
     }
 
+    /// Stores an entry, keeping when it was first added and recording that it was updated now.
     pub fn insert(&self, value: &GuildEntry) -> Result<Option<GuildEntry>, Error> {
         let old = self.get(value.guild_id)?;
+        let now = unix_now();
+        let value = GuildEntry {
+            added_at: old
+                .as_ref()
+                .and_then(|old| old.added_at)
+                .or(value.added_at)
+                .or(Some(now)),
+            updated_at: Some(now),
+            ..value.clone()
+        };
+        self.store(&value, old.as_ref())?;
+        Ok(old)
+    }
+
+    fn store(&self, value: &GuildEntry, old: Option<&GuildEntry>) -> Result<(), Error> {
+        // Reports are about the invite that was shown, so they no longer apply once it changes.
+        if old.is_none_or(|old| old.invite_url != value.invite_url) {
+            self.database.execute(
+                "DELETE FROM invite_reports WHERE guild_id = ?;",
+                params![value.guild_id as i64],
+            )?;
+        }
 
         self.database.execute(
-            "INSERT OR REPLACE INTO guilds (guild_id, name, invite_url, icon_url)
-             VALUES (?, ?, ?, ?);",
+            "INSERT OR REPLACE INTO guilds
+                (guild_id, name, invite_url, icon_url, description,
+                 language, nsfw, banner_url, added_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
             params![
                 value.guild_id as i64,
                 value.name,
                 value.invite_url.as_ref().map(|u| u.to_string()),
-                value.icon_url.as_ref().map(|u| u.to_string())
+                value.icon_url.as_ref().map(|u| u.to_string()),
+                value.description,
+                value.language,
+                value.nsfw,
+                value.banner_url.as_ref().map(|u| u.to_string()),
+                value.added_at,
+                value.updated_at
             ],
         )?;
-        Ok(old)
+        self.database.execute(
+            "DELETE FROM guild_tags WHERE guild_id = ?;",
+            params![value.guild_id as i64],
+        )?;
+        for tag in &value.tags {
+            self.database.execute(
+                "INSERT OR IGNORE INTO guild_tags (guild_id, tag) VALUES (?, ?);",
+                params![value.guild_id as i64, tag],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Applies `patch` to an existing entry and returns the entry before and after the update, as
+    /// stored, or `None` if there is no entry for `guild_id`.
+    pub fn update(
+        &self,
+        guild_id: u64,
+        patch: &GuildPatch,
+    ) -> Result<Option<(GuildEntry, GuildEntry)>, Error> {
+        let Some(old) = self.get(guild_id)? else {
+            return Ok(None);
+        };
+        self.insert(&patch.apply(&old))?;
+        Ok(self.get(guild_id)?.map(|new| (old, new)))
     }
 
     pub fn remove(&self, guild_id: u64) -> Result<Option<GuildEntry>, Error> {
@@ -49,83 +175,474 @@ impl Database {
                 "DELETE FROM guilds WHERE guild_id = ?;",
                 params![guild_id as i64],
             )?;
+            self.database.execute(
+                "DELETE FROM guild_tags WHERE guild_id = ?;",
+                params![guild_id as i64],
+            )?;
         }
         Ok(old)
     }
 
     pub fn get(&self, guild_id: u64) -> Result<Option<GuildEntry>, Error> {
-        let mut stmt = self.database.prepare(
-            "SELECT name, guild_id, invite_url, icon_url
-             FROM guilds WHERE guild_id = ?;",
-        )?;
+        let mut stmt = self.database.prepare(&format!(
+            "SELECT {GUILD_COLUMNS} FROM guilds WHERE guild_id = ?;"
+        ))?;
         let mut rows = stmt.query(params![guild_id as i64])?;
 
         if let Some(row) = rows.next()? {
-            Ok(Some(GuildEntry {
-                name: row.get::<_, String>(0)?,
-                guild_id: row.get::<_, i64>(1)? as u64,
-                invite_url: row
-                    .get::<_, Option<String>>(2)?
-                    .and_then(|s| Url::parse(&s).ok()),
-                icon_url: row
-                    .get::<_, Option<String>>(3)?
-                    .and_then(|s| Url::parse(&s).ok()),
-            }))
+            Ok(Some(guild_from_row(row)?))
         } else {
             Ok(None)
         }
     }
 
     pub fn search(&self, name: &str) -> Result<Vec<GuildEntry>, Error> {
-        let mut stmt = self.database.prepare(
-            "SELECT name, guild_id, invite_url, icon_url
-             FROM guilds WHERE name LIKE ?;",
-        )?;
+        let mut stmt = self.database.prepare(&format!(
+            "SELECT {GUILD_COLUMNS} FROM guilds WHERE name LIKE ?;"
+        ))?;
         let mut rows = stmt.query(params![format!("%{}%", name)])?;
 
         let mut results = Vec::new();
         while let Some(row) = rows.next()? {
-            results.push(GuildEntry {
-                name: row.get::<_, String>(0)?,
-                guild_id: row.get::<_, i64>(1)? as u64,
-                invite_url: row
-                    .get::<_, Option<String>>(2)?
-                    .and_then(|s| Url::parse(&s).ok()),
-                icon_url: row
-                    .get::<_, Option<String>>(3)?
-                    .and_then(|s| Url::parse(&s).ok()),
-            });
+            results.push(guild_from_row(row)?);
         }
         Ok(results)
     }
 
     pub fn export(&self) -> Result<Vec<GuildEntry>, Error> {
+        let mut stmt = self
+            .database
+            .prepare(&format!("SELECT {GUILD_COLUMNS} FROM guilds;"))?;
+        let mut rows = stmt.query([])?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next()? {
+            results.push(guild_from_row(row)?);
+        }
+        Ok(results)
+    }
+
+    /// Returns every tag in use with the number of entries carrying it, most used first.
+    pub fn tags(&self) -> Result<Vec<(String, u64)>, Error> {
         let mut stmt = self.database.prepare(
-            "SELECT name, guild_id, invite_url, icon_url
-             FROM guilds;",
+            "SELECT tag, COUNT(*) AS entries FROM guild_tags
+             WHERE guild_id IN (SELECT guild_id FROM guilds)
+             GROUP BY tag
+             ORDER BY entries DESC, tag;",
         )?;
         let mut rows = stmt.query([])?;
 
         let mut results = Vec::new();
         while let Some(row) = rows.next()? {
-            results.push(GuildEntry {
-                name: row.get::<_, String>(0)?,
-                guild_id: row.get::<_, i64>(1)? as u64,
-                invite_url: row
-                    .get::<_, Option<String>>(2)?
-                    .and_then(|s| Url::parse(&s).ok()),
-                icon_url: row
-                    .get::<_, Option<String>>(3)?
-                    .and_then(|s| Url::parse(&s).ok()),
+            results.push((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64));
+        }
+        Ok(results)
+    }
+
+    /// Returns the entries tagged with `tag`, by name.
+    pub fn tagged(&self, tag: &str) -> Result<Vec<GuildEntry>, Error> {
+        let mut stmt = self.database.prepare(&format!(
+            "SELECT {GUILD_COLUMNS} FROM guilds
+             WHERE guild_id IN (SELECT guild_id FROM guild_tags WHERE tag = ?)
+             ORDER BY name;"
+        ))?;
+        let mut rows = stmt.query(params![tag])?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next()? {
+            results.push(guild_from_row(row)?);
+        }
+        Ok(results)
+    }
+
+    /// Records a lookup of a guild ID that is not in the directory, from the message at
+    /// `message_link`.
+    pub fn record_miss(&self, guild_id: u64, message_link: Option<&str>) -> Result<(), Error> {
+        let now = unix_now();
+        self.database.execute(
+            "INSERT INTO lookup_misses (guild_id, lookups, last_seen)
+             VALUES (?, 1, ?)
+             ON CONFLICT (guild_id) DO UPDATE SET
+                lookups = lookups + 1,
+                last_seen = excluded.last_seen;",
+            params![guild_id as i64, now],
+        )?;
+        if let Some(message_link) = message_link {
+            self.database.execute(
+                "INSERT INTO lookup_links (guild_id, message_link, seen_at) VALUES (?, ?, ?)
+                 ON CONFLICT (guild_id, message_link) DO UPDATE SET seen_at = excluded.seen_at;",
+                params![guild_id as i64, message_link, now],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the most looked up guild IDs that are still not in the directory.
+    pub fn wanted(&self, limit: usize) -> Result<Vec<LookupMiss>, Error> {
+        let mut stmt = self.database.prepare(
+            "SELECT guild_id, lookups, last_seen
+             FROM lookup_misses
+             WHERE guild_id NOT IN (SELECT guild_id FROM guilds)
+             ORDER BY lookups DESC, last_seen DESC
+             LIMIT ?;",
+        )?;
+        let mut rows = stmt.query(params![limit as i64])?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next()? {
+            let guild_id = row.get::<_, i64>(0)? as u64;
+            results.push(LookupMiss {
+                guild_id,
+                lookups: row.get::<_, i64>(1)? as u64,
+                last_seen: row.get::<_, i64>(2)?,
+                message_links: self.lookup_links(guild_id)?,
             });
         }
         Ok(results)
     }
 
+    fn lookup_links(&self, guild_id: u64) -> Result<Vec<String>, Error> {
+        let mut stmt = self.database.prepare(
+            "SELECT message_link FROM lookup_links WHERE guild_id = ?
+             ORDER BY seen_at DESC, message_link;",
+        )?;
+        let links = stmt.query_map(params![guild_id as i64], |row| row.get::<_, String>(0))?;
+        links.collect()
+    }
+
+    /// Stores a pending suggestion and returns its ID.
+    pub fn insert_suggestion(&self, entry: &GuildEntry, submitter_id: u64) -> Result<u64, Error> {
+        let id = self.database.query_row(
+            "INSERT INTO suggestions
+                (id, guild_id, name, invite_url, icon_url, description,
+                 language, tags, nsfw, banner_url, submitter_id, status, created_at)
+             SELECT COALESCE(MAX(id), 0) + 1, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
+             FROM suggestions
+             RETURNING id;",
+            params![
+                entry.guild_id as i64,
+                entry.name,
+                entry.invite_url.as_ref().map(|u| u.to_string()),
+                entry.icon_url.as_ref().map(|u| u.to_string()),
+                entry.description,
+                entry.language,
+                (!entry.tags.is_empty()).then(|| entry.tags.join(",")),
+                entry.nsfw,
+                entry.banner_url.as_ref().map(|u| u.to_string()),
+                submitter_id as i64,
+                SuggestionStatus::Pending.as_str(),
+                unix_now()
+            ],
+            |row| row.get::<_, i64>(0),
+        )?;
+        Ok(id as u64)
+    }
+
+    pub fn get_suggestion(&self, id: u64) -> Result<Option<Suggestion>, Error> {
+        let mut stmt = self.database.prepare(
+            "SELECT name, guild_id, invite_url, icon_url, description,
+                    language, nsfw, banner_url, NULL, NULL, tags,
+                    id, submitter_id, status, reviewer_id, created_at, decided_at
+             FROM suggestions WHERE id = ?;",
+        )?;
+        let mut rows = stmt.query(params![id as i64])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Suggestion {
+                entry: guild_from_row(row)?,
+                id: row.get::<_, i64>(11)? as u64,
+                submitter_id: row.get::<_, i64>(12)? as u64,
+                status: SuggestionStatus::from_name(&row.get::<_, String>(13)?),
+                reviewer_id: row.get::<_, Option<i64>>(14)?.map(|id| id as u64),
+                created_at: row.get::<_, i64>(15)?,
+                decided_at: row.get::<_, Option<i64>>(16)?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Deletes a suggestion, e.g. one that could not be posted for review.
+    pub fn delete_suggestion(&self, id: u64) -> Result<(), Error> {
+        self.database
+            .execute("DELETE FROM suggestions WHERE id = ?;", params![id as i64])?;
+        Ok(())
+    }
+
+    /// Records the decision on a pending suggestion. Returns the updated suggestion, or `None`
+    /// if it does not exist or was already decided.
+    pub fn decide_suggestion(
+        &self,
+        id: u64,
+        status: SuggestionStatus,
+        reviewer_id: u64,
+    ) -> Result<Option<Suggestion>, Error> {
+        let updated = self.database.execute(
+            "UPDATE suggestions SET status = ?, reviewer_id = ?, decided_at = ?
+             WHERE id = ? AND status = ?;",
+            params![
+                status.as_str(),
+                reviewer_id as i64,
+                unix_now(),
+                id as i64,
+                SuggestionStatus::Pending.as_str()
+            ],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        self.get_suggestion(id)
+    }
+
+    /// Records a report of a broken invite and returns the number of distinct users that
+    /// reported the entry. Returns `None` if the user had already reported it, in which case
+    /// their report is replaced.
+    pub fn insert_report(
+        &self,
+        guild_id: u64,
+        reporter_id: u64,
+        reason: Option<&str>,
+        invite_valid: bool,
+    ) -> Result<Option<u64>, Error> {
+        let updated = self.database.execute(
+            "UPDATE invite_reports SET reason = ?, invite_valid = ?, created_at = ?
+             WHERE guild_id = ? AND reporter_id = ?;",
+            params![
+                reason,
+                invite_valid,
+                unix_now(),
+                guild_id as i64,
+                reporter_id as i64
+            ],
+        )?;
+        if updated > 0 {
+            return Ok(None);
+        }
+
+        self.database.execute(
+            "INSERT INTO invite_reports
+                (id, guild_id, reporter_id, reason, invite_valid, created_at)
+             SELECT COALESCE(MAX(id), 0) + 1, ?, ?, ?, ?, ?
+             FROM invite_reports;",
+            params![
+                guild_id as i64,
+                reporter_id as i64,
+                reason,
+                invite_valid,
+                unix_now()
+            ],
+        )?;
+        let reports = self.database.query_row(
+            "SELECT COUNT(*) FROM invite_reports WHERE guild_id = ?;",
+            params![guild_id as i64],
+            |row| row.get::<_, i64>(0),
+        )?;
+        Ok(Some(reports as u64))
+    }
+
+    /// Stores exported entries as they are, only stamping those exported without timestamps.
     pub fn import(&self, entries: &[GuildEntry]) -> Result<(), Error> {
+        let now = unix_now();
         for entry in entries {
-            self.insert(entry)?;
+            let added_at = entry.added_at.unwrap_or(now);
+            let entry = GuildEntry {
+                added_at: Some(added_at),
+                updated_at: Some(entry.updated_at.unwrap_or(added_at)),
+                ..entry.clone()
+            };
+            self.store(&entry, self.get(entry.guild_id)?.as_ref())?;
         }
         Ok(())
     }
 }
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Reads an entry from the columns of [`GUILD_COLUMNS`]. Suggestions select the same columns, with
+/// their tags joined by commas and no timestamps.
+fn guild_from_row(row: &Row) -> Result<GuildEntry, Error> {
+    Ok(GuildEntry {
+        name: row.get::<_, String>(0)?,
+        guild_id: row.get::<_, i64>(1)? as u64,
+        invite_url: row
+            .get::<_, Option<String>>(2)?
+            .and_then(|s| Url::parse(&s).ok()),
+        icon_url: row
+            .get::<_, Option<String>>(3)?
+            .and_then(|s| Url::parse(&s).ok()),
+        description: row.get::<_, Option<String>>(4)?,
+        language: row.get::<_, Option<String>>(5)?,
+        nsfw: row.get::<_, Option<bool>>(6)?.unwrap_or(false),
+        banner_url: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|s| Url::parse(&s).ok()),
+        added_at: row.get::<_, Option<i64>>(8)?,
+        updated_at: row.get::<_, Option<i64>>(9)?,
+        tags: row
+            .get::<_, Option<String>>(10)?
+            .map(|tags| tags.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    /// A path in a fresh temporary directory, removed when dropped.
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "guildly-{name}-{}-{}",
+                std::process::id(),
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_nanos()
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir.join("guildly.db"))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.0.parent().unwrap());
+        }
+    }
+
+    fn entry(guild_id: u64) -> GuildEntry {
+        GuildEntry {
+            name: format!("Server {guild_id}"),
+            guild_id,
+            invite_url: Some(Url::parse("https://discord.gg/rust").unwrap()),
+            icon_url: None,
+            description: Some("About".to_string()),
+            language: Some("en".to_string()),
+            tags: vec!["rust".to_string()],
+            nsfw: false,
+            banner_url: None,
+            added_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn reopens_after_a_crash() {
+        let path = TempPath::new("crash");
+        let database = Database::open(&path.0).unwrap();
+        database.insert(&entry(1)).unwrap();
+        let suggestion = database.insert_suggestion(&entry(2), 3).unwrap();
+        database.insert_report(1, 3, None, true).unwrap();
+
+        // Copying the files while the database is still open leaves them as a killed process
+        // would, with the changes only in the WAL.
+        let copy = TempPath::new("crash-copy");
+        fs::copy(&path.0, &copy.0).unwrap();
+        let wal = |path: &PathBuf| PathBuf::from(format!("{}.wal", path.display()));
+        if wal(&path.0).exists() {
+            fs::copy(wal(&path.0), wal(&copy.0)).unwrap();
+        }
+        drop(database);
+
+        let reopened = Database::open(&copy.0).unwrap();
+        assert_eq!(reopened.get(1).unwrap().unwrap().name, "Server 1");
+        assert_eq!(
+            reopened
+                .get_suggestion(suggestion)
+                .unwrap()
+                .unwrap()
+                .entry
+                .guild_id,
+            2
+        );
+        assert_eq!(
+            reopened.insert_suggestion(&entry(4), 3).unwrap(),
+            suggestion + 1
+        );
+    }
+
+    #[test]
+    fn keeps_every_message_link_of_a_miss() {
+        let database = Database::open(":memory:").unwrap();
+        database
+            .record_miss(1, Some("https://discord.com/channels/2/3/4"))
+            .unwrap();
+        database.record_miss(1, None).unwrap();
+        database
+            .record_miss(1, Some("https://discord.com/channels/2/3/5"))
+            .unwrap();
+        database
+            .record_miss(1, Some("https://discord.com/channels/2/3/4"))
+            .unwrap();
+
+        let misses = database.wanted(10).unwrap();
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].lookups, 4);
+        let mut links = misses[0].message_links.clone();
+        links.sort();
+        assert_eq!(
+            links,
+            [
+                "https://discord.com/channels/2/3/4",
+                "https://discord.com/channels/2/3/5"
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_each_reporter_once() {
+        let database = Database::open(":memory:").unwrap();
+        assert_eq!(database.insert_report(1, 10, None, false).unwrap(), Some(1));
+        assert_eq!(database.insert_report(1, 11, None, false).unwrap(), Some(2));
+        assert_eq!(
+            database.insert_report(1, 10, Some("gone"), false).unwrap(),
+            None
+        );
+        assert_eq!(database.insert_report(2, 10, None, true).unwrap(), Some(1));
+        assert_eq!(database.insert_report(1, 12, None, false).unwrap(), Some(3));
+        assert_eq!(database.insert_report(1, 12, None, false).unwrap(), None);
+    }
+
+    #[test]
+    fn updates_only_patched_fields() {
+        let database = Database::open(":memory:").unwrap();
+        let stale = GuildEntry {
+            added_at: Some(1),
+            updated_at: Some(1),
+            ..entry(1)
+        };
+        database.store(&stale, None).unwrap();
+
+        let patch = GuildPatch {
+            name: Some("Renamed".to_string()),
+            description: Some(None),
+            ..GuildPatch::default()
+        };
+        let (old, new) = database.update(1, &patch).unwrap().unwrap();
+        assert_eq!(old, stale);
+        assert_eq!(Some(&new), database.get(1).unwrap().as_ref());
+        assert_eq!(new.name, "Renamed");
+        assert_eq!(new.description, None);
+        assert!(new.updated_at > Some(1));
+        assert_eq!(
+            new,
+            GuildEntry {
+                name: "Renamed".to_string(),
+                description: None,
+                updated_at: new.updated_at,
+                ..stale
+            }
+        );
+
+        assert_eq!(database.update(2, &patch).unwrap(), None);
+    }
+}
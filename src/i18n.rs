@@ -0,0 +1,179 @@
+use std::{collections::HashMap, fmt, sync::LazyLock};
+
+use serenity::all::{
+    CommandInteraction, ComponentInteraction, CreateCommand, CreateCommandOption, ModalInteraction,
+};
+
+/// The catalogs, which map dotted keys such as `search.either` to text. Text may contain
+/// `{name}` placeholders filled in by [`tr!`](crate::tr).
+const CATALOGS: [(Locale, &str); 2] = [
+    (Locale::English, include_str!("../locales/en.toml")),
+    (Locale::Japanese, include_str!("../locales/ja.toml")),
+];
+
+static TRANSLATIONS: LazyLock<HashMap<Locale, HashMap<String, String>>> = LazyLock::new(|| {
+    CATALOGS
+        .into_iter()
+        .map(|(locale, catalog)| {
+            let table = toml::from_str::<toml::Table>(catalog)
+                .unwrap_or_else(|err| panic!("invalid catalog {}: {err}", locale.code()));
+            let mut translations = HashMap::new();
+            flatten("", table, &mut translations);
+            (locale, translations)
+        })
+        .collect()
+});
+
+/// A language Guildly replies in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    English,
+    Japanese,
+}
+
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::English, Self::Japanese];
+
+    /// The Discord locale code, e.g. `ja`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en-US",
+            Self::Japanese => "ja",
+        }
+    }
+
+    /// Matches a Discord locale code. Every English variant maps to [`Locale::English`].
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "ja" => Some(Self::Japanese),
+            code if code == "en" || code.starts_with("en-") => Some(Self::English),
+            _ => None,
+        }
+    }
+
+    /// The locale of the user if it is supported, otherwise the locale the guild has set,
+    /// otherwise English.
+    pub fn of(interaction: &impl InteractionLocale) -> Self {
+        Self::from_code(interaction.user_locale())
+            .or_else(|| interaction.guild_locale().and_then(Self::from_code))
+            .unwrap_or_default()
+    }
+}
+
+/// Interactions, which carry the locale of the user and, in guilds, of the guild.
+pub trait InteractionLocale {
+    fn user_locale(&self) -> &str;
+    fn guild_locale(&self) -> Option<&str>;
+}
+
+macro_rules! impl_interaction_locale {
+    ($($interaction:ty),*) => {$(
+        impl InteractionLocale for $interaction {
+            fn user_locale(&self) -> &str {
+                &self.locale
+            }
+
+            fn guild_locale(&self) -> Option<&str> {
+                self.guild_locale.as_deref()
+            }
+        }
+    )*};
+}
+
+impl_interaction_locale!(CommandInteraction, ComponentInteraction, ModalInteraction);
+
+fn flatten(prefix: &str, table: toml::Table, translations: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, translations),
+            toml::Value::String(text) => {
+                translations.insert(key, text);
+            }
+            value => panic!("catalog key {key} must be text, not {}", value.type_str()),
+        }
+    }
+}
+
+/// The text of `key` in `locale`, if the catalog has it.
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    TRANSLATIONS[&locale].get(key).map(String::as_str)
+}
+
+/// The text of `key` in `locale`, falling back to English and then to the key itself.
+pub fn text(locale: Locale, key: &'static str) -> &'static str {
+    lookup(locale, key)
+        .or_else(|| lookup(Locale::English, key))
+        .unwrap_or(key)
+}
+
+/// Like [`text`], with each `{name}` placeholder replaced by its value in `args`.
+pub fn format(locale: Locale, key: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter()
+        .fold(text(locale, key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// The translations of `key` into every locale other than English, for Discord's
+/// `*_localizations` fields.
+pub fn localizations(key: &str) -> Vec<(&'static str, &'static str)> {
+    Locale::ALL
+        .into_iter()
+        .filter(|locale| *locale != Locale::English)
+        .filter_map(|locale| Some((locale.code(), lookup(locale, key)?)))
+        .collect()
+}
+
+/// Adds the translations of `commands.<name>.name` and `commands.<name>.description` to the
+/// definition of the command called `name`, registered as `registered_name`. Translated names
+/// keep the prefix the registered name adds.
+pub fn localize_command(
+    mut definition: CreateCommand,
+    name: &str,
+    registered_name: &str,
+) -> CreateCommand {
+    let prefix = registered_name.strip_suffix(name).unwrap_or_default();
+    for (code, localized) in localizations(&format!("commands.{name}.name")) {
+        definition = definition.name_localized(code, format!("{prefix}{localized}"));
+    }
+    for (code, localized) in localizations(&format!("commands.{name}.description")) {
+        definition = definition.description_localized(code, localized);
+    }
+    definition
+}
+
+/// Adds the translations of `options.<name>.description` to an option called `name`.
+pub fn localize_option(mut option: CreateCommandOption, name: &str) -> CreateCommandOption {
+    for (code, localized) in localizations(&format!("options.{name}.description")) {
+        option = option.description_localized(code, localized);
+    }
+    option
+}
+
+/// Translates a catalog key into a locale, filling in `{name}` placeholders.
+///
+/// ```
+/// use guildly::{i18n::Locale, tr};
+///
+/// assert_eq!(tr!(Locale::English, "entries.not_found"), "No Servers Found");
+/// assert_eq!(tr!(Locale::English, "rate_limit.retry", seconds = 3), "Retry in 3s.");
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($locale:expr, $key:literal) => {
+        $crate::i18n::text($locale, $key)
+    };
+    ($locale:expr, $key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::format(
+            $locale,
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
@@ -1,106 +1,553 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
 
 use serde::{Deserialize, Serialize};
 use serenity::{
     all::{
-        Colour, Command, Context, CreateCommand, CreateEmbed, CreateEmbedAuthor,
-        CreateInteractionResponse, CreateInteractionResponseMessage, EventHandler, Interaction,
-        Ready,
+        Channel, ChannelId, Colour, CommandInteraction, ComponentInteraction, Context,
+        CreateCommand, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
+        CreateInteractionResponse, CreateMessage, EventHandler, GuildId, Http, Interaction, Member,
+        Message, ModalInteraction, Ready,
     },
     async_trait,
 };
 use url::Url;
 
-use crate::commands::GuildlyCommand;
+use crate::{
+    commands::{
+        GuildlyCommand, GuildlyComponent, GuildlyModal,
+        show_menu::{create_lookup_reply, lookup_messages},
+    },
+    middleware::Middleware,
+    response::Response,
+};
+
+pub mod builder;
 
 pub mod database;
 
+pub mod i18n;
+
+pub mod link;
+
+pub mod middleware;
+
+pub use link::{DiscordLink, extract_links};
+
 pub mod commands;
 
+pub mod config;
+
+pub mod options;
+
+pub mod rate_limit;
+
+pub mod response;
+
+pub mod source;
+
+pub mod sync;
+
+pub mod validation;
+
+pub mod webhook;
+
 pub struct GuildlyHandler {
     database: database::Database,
-    link_finder: linkify::LinkFinder,
-    commands: HashMap<&'static str, Box<dyn GuildlyCommand>>,
-}
-
-fn url_filter<'a>(link: linkify::Link<'a>) -> Option<u64> {
-    let url = Url::parse(link.as_str()).unwrap();
-    if matches!(
-        url.host(),
-        Some(url::Host::Domain("discord.com" | "discordapp.com"))
-    ) {
-        let mut segments = url.path_segments().unwrap();
-        if Some("channels") == segments.next()
-            && let Some(raw_guild_id) = segments.next()
-            && let Ok(guild_id) = raw_guild_id.parse::<u64>()
-        {
-            return Some(guild_id);
+    /// Keyed by the registered name, i.e. with the command prefix.
+    commands: HashMap<String, RegisteredCommand>,
+    command_prefix: String,
+    components: HashMap<&'static str, Box<dyn GuildlyComponent>>,
+    modals: HashMap<&'static str, Box<dyn GuildlyModal>>,
+    maintainer_channel: Option<ChannelId>,
+    /// The server of the maintainer channel, looked up on first use.
+    curator_guild: OnceLock<GuildId>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    /// Limits components and modals, which do not run through the middleware.
+    component_limiter: rate_limit::RateLimiter,
+    modal_limiter: rate_limit::RateLimiter,
+    scope: sync::Scope,
+}
+
+/// A command with the middleware chain composed for it on registration.
+struct RegisteredCommand {
+    command: Box<dyn GuildlyCommand>,
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl RegisteredCommand {
+    /// Whether the reply, and the "thinking" message while deferred, is only shown to the user.
+    fn is_ephemeral(&self, interaction: &CommandInteraction) -> bool {
+        self.command.ephemeral() || response::bot_is_absent(interaction)
+    }
+
+    /// Runs the command through its middleware, awaiting `defer` if it takes too long. Returns
+    /// the response and whether the interaction was deferred.
+    async fn run<F: Future<Output = serenity::Result<()>>>(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+        defer: impl FnOnce(bool) -> F,
+    ) -> (Response, bool) {
+        let command = self.command.as_ref();
+        let (response, deferred) = response::execute_with_deferral(
+            middleware::Next::new(command, &self.middleware).run(handler, http, interaction),
+            command.slow(),
+            response::DEFER_AFTER,
+            defer(self.is_ephemeral(interaction)),
+        )
+        .await;
+        if response::bot_is_absent(interaction) {
+            (response.into_ephemeral(), deferred)
+        } else {
+            (response, deferred)
         }
     }
-    None
+}
+
+/// Finds the guild IDs linked from `text`.
+#[deprecated(note = "use `link::find_guild_ids`")]
+pub fn guild_link_finder(text: &str) -> impl Iterator<Item = u64> {
+    link::find_guild_ids(text)
+}
+
+/// Starts the custom IDs of Guildly's components and modals, so that they don't collide with the
+/// host's own.
+const CUSTOM_ID_NAMESPACE: &str = "guildly:";
+
+/// Creates the custom ID of a component or modal handled by the one with `prefix`, carrying
+/// `args`.
+pub(crate) fn custom_id(prefix: &str, args: impl std::fmt::Display) -> String {
+    format!("{CUSTOM_ID_NAMESPACE}{prefix}:{args}")
+}
+
+/// Components and modals are routed by the part of their custom ID between the namespace and the
+/// next `:`. Returns `None` for custom IDs Guildly did not create.
+fn custom_id_prefix(custom_id: &str) -> Option<&str> {
+    custom_id
+        .strip_prefix(CUSTOM_ID_NAMESPACE)?
+        .split(':')
+        .next()
+}
+
+/// The arguments of a custom ID created by [`custom_id`], i.e. everything after its prefix.
+pub(crate) fn custom_id_args(custom_id: &str) -> &str {
+    custom_id
+        .strip_prefix(CUSTOM_ID_NAMESPACE)
+        .and_then(|custom_id| custom_id.split_once(':'))
+        .map_or("", |(_, args)| args)
 }
 
 impl GuildlyHandler {
+    /// Creates a handler without commands, with the [`Logging`](middleware::Logging) and
+    /// [`RateLimiting`](rate_limit::RateLimiting) middleware.
     pub fn new(database: database::Database) -> Self {
-        let mut link_finder = linkify::LinkFinder::new();
-        link_finder.kinds(&[linkify::LinkKind::Url]);
-        link_finder.url_must_have_scheme(false);
         Self {
             database,
-            link_finder,
             commands: HashMap::new(),
+            command_prefix: String::new(),
+            components: HashMap::new(),
+            modals: HashMap::new(),
+            maintainer_channel: None,
+            curator_guild: OnceLock::new(),
+            middleware: vec![
+                Arc::new(middleware::Logging),
+                Arc::new(rate_limit::RateLimiting::new()),
+            ],
+            component_limiter: rate_limit::RateLimiter::new(),
+            modal_limiter: rate_limit::RateLimiter::new(),
+            scope: sync::Scope::Global,
+        }
+    }
+
+    pub fn builder(database: database::Database) -> builder::GuildlyHandlerBuilder {
+        builder::GuildlyHandlerBuilder::new(database)
+    }
+
+    /// Creates a handler with the builtin commands enabled by `config`, and every builtin
+    /// component and modal.
+    pub fn from_config(database: database::Database, config: &config::Config) -> Self {
+        let mut builder = Self::builder(database).default_commands(false);
+        if let Some(maintainer_channel) = config.maintainer_channel {
+            builder = builder.maintainer_channel(ChannelId::new(maintainer_channel));
+        }
+        if let Some(dev_guild) = config.dev_guild {
+            builder = builder.dev_guild(GuildId::new(dev_guild));
+        }
+        for command in commands::builtin_commands() {
+            if config.is_enabled(command.name()) {
+                builder = builder.command(command);
+            }
         }
+        builder.build()
     }
+    /// Registers `command`, wrapped in the handler's middleware followed by its own.
     pub fn register(&mut self, command: Box<dyn GuildlyCommand>) {
-        self.commands.insert(command.name(), command);
+        let middleware = self
+            .middleware
+            .iter()
+            .cloned()
+            .chain(command.middleware())
+            .collect();
+        self.commands.insert(
+            format!("{}{}", self.command_prefix, command.name()),
+            RegisteredCommand {
+                command,
+                middleware,
+            },
+        );
+    }
+    /// Adds middleware run around every command registered after this call.
+    pub fn add_middleware(&mut self, middleware: impl Middleware + 'static) {
+        self.middleware.push(Arc::new(middleware));
+    }
+    pub fn register_component(&mut self, component: Box<dyn GuildlyComponent>) {
+        self.components.insert(component.prefix(), component);
+    }
+    pub fn register_modal(&mut self, modal: Box<dyn GuildlyModal>) {
+        self.modals.insert(modal.prefix(), modal);
+    }
+    /// See [`GuildlyHandlerBuilder::maintainer_channel`](builder::GuildlyHandlerBuilder::maintainer_channel).
+    pub fn set_maintainer_channel(&mut self, channel: ChannelId) {
+        self.maintainer_channel = Some(channel);
+        self.curator_guild = OnceLock::new();
+    }
+    /// See [`GuildlyHandlerBuilder::dev_guild`](builder::GuildlyHandlerBuilder::dev_guild).
+    pub fn set_dev_guild(&mut self, guild: GuildId) {
+        self.scope = sync::Scope::Guild(guild);
+    }
+    /// Whether the member who sent an interaction in `guild_id` may change the directory. The
+    /// directory is shared by every server, so curators need
+    /// [`CURATOR_PERMISSIONS`](commands::CURATOR_PERMISSIONS) in the server of the maintainer
+    /// channel, or without one in the dev guild. Otherwise nobody is a curator.
+    pub async fn is_curator(
+        &self,
+        http: &Http,
+        guild_id: Option<GuildId>,
+        member: Option<&Member>,
+    ) -> bool {
+        let has_permissions = member
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.contains(commands::CURATOR_PERMISSIONS));
+        has_permissions && guild_id.is_some() && self.curator_guild(http).await == guild_id
+    }
+
+    async fn curator_guild(&self, http: &Http) -> Option<GuildId> {
+        if let Some(guild_id) = self.curator_guild.get() {
+            return Some(*guild_id);
+        }
+        let guild_id = match (self.maintainer_channel, self.scope) {
+            (Some(channel), _) => match channel.to_channel(http).await {
+                Ok(Channel::Guild(channel)) => channel.guild_id,
+                Ok(_) => return None,
+                Err(why) => {
+                    tracing::warn!("Failed to look up the maintainer channel: {why:?}");
+                    return None;
+                }
+            },
+            (None, sync::Scope::Guild(guild_id)) => guild_id,
+            (None, sync::Scope::Global) => return None,
+        };
+        Some(*self.curator_guild.get_or_init(|| guild_id))
+    }
+    /// The definitions of the registered commands, sorted by name. A host bot that registers
+    /// its own commands should include these, since registering replaces every command.
+    pub fn definitions(&self) -> Vec<CreateCommand> {
+        let mut commands = self.commands.iter().collect::<Vec<_>>();
+        commands.sort_by_key(|(name, _)| name.as_str());
+        commands
+            .into_iter()
+            .map(|(name, registered)| {
+                commands::create_definition(registered.command.as_ref(), name)
+            })
+            .collect()
+    }
+    /// Registers the commands' [`definitions`](Self::definitions) with Discord.
+    pub async fn sync_commands(&self, http: &Http) -> serenity::Result<sync::SyncReport> {
+        sync::sync(http, self.scope, self.definitions()).await
+    }
+    /// Runs the component `interaction` was sent by, unless the user exceeded its rate limits.
+    /// Returns `None` if no registered component handles it.
+    async fn run_component(
+        &self,
+        http: &Http,
+        interaction: &ComponentInteraction,
+    ) -> Option<CreateInteractionResponse> {
+        let component = self
+            .components
+            .get(custom_id_prefix(&interaction.data.custom_id)?)?;
+        let checked = self.component_limiter.check(
+            component.prefix(),
+            &component.rate_limits(),
+            interaction.user.id.get(),
+            interaction.guild_id.map(|guild_id| guild_id.get()),
+            Instant::now(),
+        );
+        Some(match checked {
+            Ok(()) => component.execute(self, http, interaction).await,
+            Err(retry_after) => {
+                rate_limit::create_rate_limited_message(retry_after, i18n::Locale::of(interaction))
+            }
+        })
+    }
+    /// Like [`run_component`](Self::run_component), for modals.
+    async fn run_modal(
+        &self,
+        http: &Http,
+        interaction: &ModalInteraction,
+    ) -> Option<CreateInteractionResponse> {
+        let modal = self
+            .modals
+            .get(custom_id_prefix(&interaction.data.custom_id)?)?;
+        let checked = self.modal_limiter.check(
+            modal.prefix(),
+            &modal.rate_limits(),
+            interaction.user.id.get(),
+            interaction.guild_id.map(|guild_id| guild_id.get()),
+            Instant::now(),
+        );
+        Some(match checked {
+            Ok(()) => modal.execute(self, http, interaction).await,
+            Err(retry_after) => {
+                rate_limit::create_rate_limited_message(retry_after, i18n::Locale::of(interaction))
+            }
+        })
+    }
+    /// Handles `interaction` if it is one of Guildly's commands, components or modals, and
+    /// returns whether it was.
+    pub async fn handle_interaction(&self, ctx: &Context, interaction: &Interaction) -> bool {
+        match interaction {
+            Interaction::Command(command) => {
+                let Some(registered) = self.commands.get(command.data.name.as_str()) else {
+                    return false;
+                };
+                let (response, deferred) = registered
+                    .run(self, &ctx.http, command, |ephemeral| {
+                        response::defer(&ctx.http, command, ephemeral)
+                    })
+                    .await;
+
+                if let Err(why) = response.send(&ctx.http, command, deferred).await {
+                    tracing::error!("Failed to respond to {}: {why:?}", command.data.name);
+                }
+            }
+            Interaction::Component(component) => {
+                let Some(response) = self.run_component(&ctx.http, component).await else {
+                    return false;
+                };
+
+                if let Err(why) = component.create_response(&ctx.http, response).await {
+                    tracing::error!("Failed to respond to {}: {why:?}", component.data.custom_id);
+                }
+            }
+            Interaction::Modal(modal) => {
+                let Some(response) = self.run_modal(&ctx.http, modal).await else {
+                    return false;
+                };
+
+                if let Err(why) = modal.create_response(&ctx.http, response).await {
+                    tracing::error!("Failed to respond to {}: {why:?}", modal.data.custom_id);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+    /// Replies to a message mentioning the bot with the servers linked in it, or in the message
+    /// it replies to. Returns whether it replied.
+    pub async fn handle_message(&self, ctx: &Context, message: &Message) -> bool {
+        if message.author.bot || !message.mentions_me(ctx).await.unwrap_or(false) {
+            return false;
+        }
+        let messages = std::iter::once(message)
+            .chain(message.referenced_message.as_deref())
+            .collect::<Vec<_>>();
+        let (found, unlisted) =
+            lookup_messages(self, Some(&ctx.http), &messages, message.guild_id).await;
+        if found.is_empty() && unlisted.is_empty() {
+            return false;
+        }
+
+        // Messages carry no locale, unlike interactions.
+        let (embed, components) = create_lookup_reply(found, &unlisted, i18n::Locale::default());
+        let reply = CreateMessage::new()
+            .embed(embed)
+            .components(components)
+            .reference_message(message);
+        if let Err(why) = message.channel_id.send_message(&ctx.http, reply).await {
+            tracing::error!("Failed to reply to {}: {why:?}", message.id);
+        }
+        true
     }
+
+    /// Finds the guild IDs linked from `text`.
+    #[deprecated(note = "use `link::find_guild_ids`")]
     pub fn guild_link_finder(&self, text: &str) -> impl Iterator<Item = u64> {
-        self.link_finder.links(text).filter_map(url_filter)
+        link::find_guild_ids(text)
     }
 }
 
 #[async_trait]
 impl EventHandler for GuildlyHandler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            if let Some(guildly_command) = self.commands.get(command.data.name.as_str()) {
-                let embed = guildly_command.execute(self, &ctx, &command).await;
-
-                let _ = command
-                    .create_response(
-                        &ctx.http,
-                        CreateInteractionResponse::Message(
-                            CreateInteractionResponseMessage::new().add_embed(embed),
-                        ),
-                    )
-                    .await
-                    .unwrap();
-            } else {
-                println!("Not Found Command: {}", command.data.name);
-            }
+        if !self.handle_interaction(&ctx, &interaction).await {
+            tracing::warn!("Not Found Interaction: {:?}", interaction.kind());
         }
     }
 
+    async fn message(&self, ctx: Context, message: Message) {
+        self.handle_message(&ctx, &message).await;
+    }
+
     async fn ready(&self, ctx: Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
-        for command in self.commands.values() {
-            let command = Command::create_global_command(
-                &ctx.http,
-                command.create_command(CreateCommand::new(command.name())),
-            )
-            .await
-            .unwrap();
-            println!("created command {:?}", command);
+        tracing::info!("{} is connected!", ready.user.name);
+        match self.sync_commands(&ctx.http).await {
+            Ok(report) => tracing::info!(
+                "Synced commands: created {:?}, updated {:?}, deleted {:?}",
+                report.created,
+                report.updated,
+                report.deleted
+            ),
+            Err(why) => tracing::error!("Failed to sync commands: {why:?}"),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GuildEntry {
     pub name: String,
     pub guild_id: u64,
     pub invite_url: Option<Url>,
     pub icon_url: Option<Url>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The main language spoken in the server, e.g. `ja`.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Lowercase and sorted.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub nsfw: bool,
+    #[serde(default)]
+    pub banner_url: Option<Url>,
+    /// Unix timestamps, in seconds. Set by the database, and missing in entries exported before
+    /// they were recorded.
+    #[serde(default)]
+    pub added_at: Option<i64>,
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+}
+
+/// A guild ID that users looked up but that is not in the directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LookupMiss {
+    pub guild_id: u64,
+    pub lookups: u64,
+    /// Unix timestamp, in seconds, of the last lookup.
+    pub last_seen: i64,
+    /// Links to the messages it was looked up from, most recent first.
+    pub message_links: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl SuggestionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_name(value: &str) -> Self {
+        match value {
+            "approved" => Self::Approved,
+            "rejected" => Self::Rejected,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// An entry proposed with `/suggest`, together with who proposed and reviewed it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub id: u64,
+    pub entry: GuildEntry,
+    pub submitter_id: u64,
+    pub status: SuggestionStatus,
+    pub reviewer_id: Option<u64>,
+    /// Unix timestamps, in seconds.
+    pub created_at: i64,
+    pub decided_at: Option<i64>,
+}
+
+/// A partial update of a [`GuildEntry`]. `None` leaves a field untouched, while `Some(None)`
+/// clears an optional field.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GuildPatch {
+    pub name: Option<String>,
+    pub invite_url: Option<Option<Url>>,
+    pub icon_url: Option<Option<Url>>,
+    pub description: Option<Option<String>>,
+    pub language: Option<Option<String>>,
+    pub tags: Option<Vec<String>>,
+    pub nsfw: Option<bool>,
+    pub banner_url: Option<Option<Url>>,
+}
+
+impl GuildPatch {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.invite_url.is_none()
+            && self.icon_url.is_none()
+            && self.description.is_none()
+            && self.language.is_none()
+            && self.tags.is_none()
+            && self.nsfw.is_none()
+            && self.banner_url.is_none()
+    }
+
+    pub fn apply(&self, entry: &GuildEntry) -> GuildEntry {
+        GuildEntry {
+            name: self.name.clone().unwrap_or_else(|| entry.name.clone()),
+            guild_id: entry.guild_id,
+            invite_url: self
+                .invite_url
+                .clone()
+                .unwrap_or_else(|| entry.invite_url.clone()),
+            icon_url: self
+                .icon_url
+                .clone()
+                .unwrap_or_else(|| entry.icon_url.clone()),
+            description: self
+                .description
+                .clone()
+                .unwrap_or_else(|| entry.description.clone()),
+            language: self
+                .language
+                .clone()
+                .unwrap_or_else(|| entry.language.clone()),
+            tags: self.tags.clone().unwrap_or_else(|| entry.tags.clone()),
+            nsfw: self.nsfw.unwrap_or(entry.nsfw),
+            banner_url: self
+                .banner_url
+                .clone()
+                .unwrap_or_else(|| entry.banner_url.clone()),
+            ..entry.clone()
+        }
+    }
 }
 
 pub struct Color;
@@ -111,7 +558,7 @@ impl Color {
     pub const ERROR: Colour = Colour::RED;
 }
 
-pub fn create_embed_from_entry(entry: &GuildEntry) -> CreateEmbed {
+pub fn create_embed_from_entry(entry: &GuildEntry, locale: i18n::Locale) -> CreateEmbed {
     let mut auther = CreateEmbedAuthor::new(&entry.name);
     if let Some(invite_url) = &entry.invite_url {
         auther = auther.url(invite_url.as_str());
@@ -120,30 +567,98 @@ pub fn create_embed_from_entry(entry: &GuildEntry) -> CreateEmbed {
         auther = auther.icon_url(icon_url.as_str());
     }
 
-    CreateEmbed::new().color(Color::INFO).author(auther)
+    let mut embed = CreateEmbed::new().color(Color::INFO).author(auther);
+    if let Some(description) = &entry.description {
+        embed = embed.description(description);
+    }
+    if let Some(language) = &entry.language {
+        embed = embed.field(tr!(locale, "entry.language"), language, true);
+    }
+    if !entry.tags.is_empty() {
+        let tags = entry.tags.iter().map(|tag| format!("`{tag}`"));
+        embed = embed.field(
+            tr!(locale, "entry.tags"),
+            tags.collect::<Vec<_>>().join(" "),
+            true,
+        );
+    }
+    if entry.nsfw {
+        embed = embed.field(
+            tr!(locale, "entry.nsfw"),
+            tr!(locale, "entry.nsfw_notice"),
+            true,
+        );
+    }
+    if let Some(added_at) = entry.added_at {
+        embed = embed.field(
+            tr!(locale, "entry.added"),
+            format!("<t:{added_at}:D>"),
+            true,
+        );
+    }
+    if let Some(updated_at) = entry.updated_at.filter(|&at| Some(at) != entry.added_at) {
+        embed = embed.field(
+            tr!(locale, "entry.updated"),
+            format!("<t:{updated_at}:R>"),
+            true,
+        );
+    }
+    if let Some(banner_url) = &entry.banner_url {
+        embed = embed.image(banner_url.as_str());
+    }
+    embed
+}
+
+/// Formats the name of an entry as a markdown link to its invite, if it has one.
+pub fn create_entry_link(entry: &GuildEntry) -> String {
+    if let Some(invite_url) = &entry.invite_url {
+        format!("[{}]({})", entry.name, invite_url.as_str())
+    } else {
+        entry.name.clone()
+    }
 }
 
-pub fn create_embed_from_entries(entries: &[GuildEntry]) -> CreateEmbed {
+pub fn create_embed_from_entries(entries: &[GuildEntry], locale: i18n::Locale) -> CreateEmbed {
     if entries.is_empty() {
         CreateEmbed::new()
             .color(Color::WARN)
-            .title("No Servers Found")
+            .title(tr!(locale, "entries.not_found"))
     } else if entries.len() == 1 {
-        create_embed_from_entry(&entries[0])
+        create_embed_from_entry(&entries[0], locale)
     } else {
         CreateEmbed::new()
             .color(Color::INFO)
-            .title("Servers")
-            .fields(entries.iter().map(|entry| {
-                (
-                    if let Some(invite_url) = &entry.invite_url {
-                        format!("[{}]({})", entry.name, invite_url.as_str())
-                    } else {
-                        entry.name.clone()
-                    },
-                    "",
-                    false,
-                )
-            }))
+            .title(tr!(locale, "entries.servers"))
+            .fields(
+                entries
+                    .iter()
+                    .map(|entry| (create_entry_link(entry), "", false)),
+            )
     }
 }
+
+/// How many entries a page of [`create_embed_from_page`] lists.
+pub const ENTRIES_PER_PAGE: usize = 10;
+
+/// The number of pages `entries` take up, at least one.
+pub fn page_count(entries: &[GuildEntry]) -> usize {
+    entries.len().div_ceil(ENTRIES_PER_PAGE).max(1)
+}
+
+/// Creates the embed listing page `page`, counted from 0, of `entries`, numbered in the footer.
+pub fn create_embed_from_page(
+    entries: &[GuildEntry],
+    page: usize,
+    locale: i18n::Locale,
+) -> CreateEmbed {
+    let page_entries = entries
+        .chunks(ENTRIES_PER_PAGE)
+        .nth(page)
+        .unwrap_or_default();
+    create_embed_from_entries(page_entries, locale).footer(CreateEmbedFooter::new(tr!(
+        locale,
+        "entries.page",
+        page = page + 1,
+        pages = page_count(entries)
+    )))
+}
@@ -0,0 +1,401 @@
+use std::sync::LazyLock;
+
+use url::{Host, Url};
+
+static LINK_FINDER: LazyLock<linkify::LinkFinder> = LazyLock::new(|| {
+    let mut link_finder = linkify::LinkFinder::new();
+    link_finder.kinds(&[linkify::LinkKind::Url]);
+    link_finder.url_must_have_scheme(false);
+    link_finder
+});
+
+/// Characters that wrap links in Discord markdown, e.g. `<...>`, `[...](...)`, `**...**` or
+/// `||...||`.
+const WRAPPING_CHARS: &[char] = &[
+    '<', '>', '(', ')', '[', ']', '*', '_', '~', '|', '`', '"', '\'',
+];
+
+/// Subdomains that serve the same pages as `discord.com`.
+const DISCORD_SUBDOMAINS: &[&str] = &["www.", "ptb.", "canary."];
+
+/// Parses a link as found in a message into a URL, accepting links without a scheme and
+/// wrapped in markdown.
+pub fn normalize(link: &str) -> Option<Url> {
+    let link = link.trim().trim_matches(WRAPPING_CHARS);
+    if link.is_empty() {
+        return None;
+    }
+
+    let has_scheme = link
+        .split_once("://")
+        .is_some_and(|(scheme, _)| scheme.chars().all(|c| c.is_ascii_alphabetic()));
+    if has_scheme {
+        Url::parse(link).ok()
+    } else {
+        Url::parse(&format!("https://{link}")).ok()
+    }
+}
+
+/// Returns the Discord domain of `url` without any of the [`DISCORD_SUBDOMAINS`], or `None` if
+/// `url` does not point to Discord.
+fn discord_domain(url: &Url) -> Option<&str> {
+    let Some(Host::Domain(domain)) = url.host() else {
+        return None;
+    };
+    let mut domain = domain.trim_end_matches('.');
+    for subdomain in DISCORD_SUBDOMAINS {
+        if let Some(stripped) = domain.strip_prefix(subdomain) {
+            domain = stripped;
+            break;
+        }
+    }
+    matches!(domain, "discord.com" | "discordapp.com" | "discord.gg").then_some(domain)
+}
+
+fn parse_snowflake(segment: &str) -> Option<u64> {
+    segment.parse::<u64>().ok().filter(|&id| id != 0)
+}
+
+/// A link to a page of Discord.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiscordLink {
+    /// `discord.com/channels/<guild>/<channel>/<message>`
+    Message {
+        guild: u64,
+        channel: u64,
+        message: u64,
+    },
+    /// `discord.com/channels/<guild>/<channel>`
+    Channel { guild: u64, channel: u64 },
+    /// `discord.com/channels/<guild>`
+    Guild { guild: u64 },
+    /// `discord.gg/<code>` or `discord.com/invite/<code>`
+    Invite { code: String },
+    /// `discord.com/channels/@me/<channel>[/<message>]`
+    GuildDirectMessage { channel: u64, message: Option<u64> },
+    /// Any other page of Discord.
+    Unknown(Url),
+}
+
+impl DiscordLink {
+    /// Classifies a link as found in a message. Links without a scheme, with the `www.`, `ptb.`
+    /// or `canary.` subdomains and wrapped in markdown are accepted.
+    ///
+    /// Returns `None` if the link does not point to Discord.
+    pub fn parse(link: &str) -> Option<Self> {
+        let url = normalize(link)?;
+        let domain = discord_domain(&url)?;
+
+        let segments = url
+            .path_segments()
+            .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+            .unwrap_or_else(Vec::new);
+
+        let link = match (domain, segments.as_slice()) {
+            // discord.gg only serves invites, as its single path segment.
+            ("discord.gg", [code]) => Some(Self::Invite {
+                code: code.to_string(),
+            }),
+            ("discord.gg", _) => None,
+            (_, ["invite", code]) => Some(Self::Invite {
+                code: code.to_string(),
+            }),
+            (_, ["channels", "@me", channel, rest @ ..]) if rest.len() <= 1 => {
+                let channel = parse_snowflake(channel);
+                match rest.first().map(|message| parse_snowflake(message)) {
+                    None => channel.map(|channel| Self::GuildDirectMessage {
+                        channel,
+                        message: None,
+                    }),
+                    Some(message) => {
+                        channel
+                            .zip(message)
+                            .map(|(channel, message)| Self::GuildDirectMessage {
+                                channel,
+                                message: Some(message),
+                            })
+                    }
+                }
+            }
+            (_, ["channels", guild]) => parse_snowflake(guild).map(|guild| Self::Guild { guild }),
+            (_, ["channels", guild, channel]) => parse_snowflake(guild)
+                .zip(parse_snowflake(channel))
+                .map(|(guild, channel)| Self::Channel { guild, channel }),
+            (_, ["channels", guild, channel, message]) => parse_snowflake(guild)
+                .zip(parse_snowflake(channel))
+                .zip(parse_snowflake(message))
+                .map(|((guild, channel), message)| Self::Message {
+                    guild,
+                    channel,
+                    message,
+                }),
+            _ => None,
+        };
+        Some(link.unwrap_or(Self::Unknown(url)))
+    }
+
+    /// Returns the ID of the guild this link points into, if any.
+    pub fn guild_id(&self) -> Option<u64> {
+        match self {
+            Self::Message { guild, .. } | Self::Channel { guild, .. } | Self::Guild { guild } => {
+                Some(*guild)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Finds and classifies every Discord link in `text`, in the order they appear.
+pub fn extract_links(text: &str) -> impl Iterator<Item = DiscordLink> {
+    LINK_FINDER
+        .links(text)
+        .filter_map(|link| DiscordLink::parse(link.as_str()))
+}
+
+/// Finds the guild IDs of every message, channel and guild link in `text`, in the order they
+/// appear. Invite links are skipped, since the guild behind them is only known to Discord.
+pub fn find_guild_ids(text: &str) -> impl Iterator<Item = u64> {
+    extract_links(text).filter_map(|link| link.guild_id())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn message(guild: u64, channel: u64, message: u64) -> Option<DiscordLink> {
+        Some(DiscordLink::Message {
+            guild,
+            channel,
+            message,
+        })
+    }
+
+    #[test]
+    fn parses_message_links() {
+        let cases = [
+            ("https://discord.com/channels/1/2/3", message(1, 2, 3)),
+            ("http://discord.com/channels/1/2/3", message(1, 2, 3)),
+            ("https://discordapp.com/channels/1/2/3", message(1, 2, 3)),
+            ("https://discord.com/channels/1/2/3/", message(1, 2, 3)),
+            (
+                "https://discord.com/channels/1/2/3?query#fragment",
+                message(1, 2, 3),
+            ),
+        ];
+        for (link, expected) in cases {
+            assert_eq!(DiscordLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn accepts_links_without_scheme() {
+        let cases = [
+            ("discord.com/channels/1/2/3", message(1, 2, 3)),
+            ("discordapp.com/channels/1/2/3", message(1, 2, 3)),
+            (
+                "discord.gg/rust",
+                Some(DiscordLink::Invite {
+                    code: "rust".to_string(),
+                }),
+            ),
+        ];
+        for (link, expected) in cases {
+            assert_eq!(DiscordLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn strips_discord_subdomains() {
+        let cases = [
+            ("https://www.discord.com/channels/1/2/3", message(1, 2, 3)),
+            ("https://ptb.discord.com/channels/1/2/3", message(1, 2, 3)),
+            (
+                "https://canary.discord.com/channels/1/2/3",
+                message(1, 2, 3),
+            ),
+            ("canary.discordapp.com/channels/1/2/3", message(1, 2, 3)),
+            ("https://discord.com./channels/1/2/3", message(1, 2, 3)),
+            ("https://evil.discord.com/channels/1/2/3", None),
+            ("https://discord.com.evil.com/channels/1/2/3", None),
+        ];
+        for (link, expected) in cases {
+            assert_eq!(DiscordLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn unwraps_markdown() {
+        let cases = [
+            "<https://discord.com/channels/1/2/3>",
+            "[https://discord.com/channels/1/2/3]",
+            "(https://discord.com/channels/1/2/3)",
+            "**https://discord.com/channels/1/2/3**",
+            "||https://discord.com/channels/1/2/3||",
+            "`https://discord.com/channels/1/2/3`",
+            "\"https://discord.com/channels/1/2/3\"",
+            "  https://discord.com/channels/1/2/3  ",
+        ];
+        for link in cases {
+            assert_eq!(DiscordLink::parse(link), message(1, 2, 3), "{link}");
+        }
+    }
+
+    #[test]
+    fn classifies_other_pages() {
+        let invite = |code: &str| {
+            Some(DiscordLink::Invite {
+                code: code.to_string(),
+            })
+        };
+        let cases = [
+            (
+                "https://discord.com/channels/1",
+                Some(DiscordLink::Guild { guild: 1 }),
+            ),
+            (
+                "https://discord.com/channels/1/2",
+                Some(DiscordLink::Channel {
+                    guild: 1,
+                    channel: 2,
+                }),
+            ),
+            (
+                "https://discord.com/channels/@me/2",
+                Some(DiscordLink::GuildDirectMessage {
+                    channel: 2,
+                    message: None,
+                }),
+            ),
+            (
+                "https://discord.com/channels/@me/2/3",
+                Some(DiscordLink::GuildDirectMessage {
+                    channel: 2,
+                    message: Some(3),
+                }),
+            ),
+            ("https://discord.gg/rust", invite("rust")),
+            ("https://discord.com/invite/rust", invite("rust")),
+            ("https://example.com/channels/1/2/3", None),
+            ("not a link", None),
+            ("", None),
+        ];
+        for (link, expected) in cases {
+            assert_eq!(DiscordLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn only_accepts_invites_on_discord_gg() {
+        for link in [
+            "https://discord.gg/channels/1/2",
+            "https://discord.gg/channels/1/2/3",
+            "https://discord.gg/invite/rust",
+            "https://discord.gg/",
+        ] {
+            assert!(
+                matches!(DiscordLink::parse(link), Some(DiscordLink::Unknown(_))),
+                "{link}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_zero_and_invalid_ids() {
+        for link in [
+            "https://discord.com/channels/0/2/3",
+            "https://discord.com/channels/1/0/3",
+            "https://discord.com/channels/1/2/0",
+            "https://discord.com/channels/0",
+            "https://discord.com/channels/@me/0",
+            "https://discord.com/channels/a/2/3",
+            "https://discord.com/channels/1/2/99999999999999999999",
+            "https://discord.com/channels/1/2/3/4",
+        ] {
+            let link = DiscordLink::parse(link);
+            assert!(matches!(link, Some(DiscordLink::Unknown(_))), "{link:?}");
+            assert_eq!(link.and_then(|link| link.guild_id()), None);
+        }
+    }
+
+    #[test]
+    fn extracts_links_in_order() {
+        let text = "see <https://discord.com/channels/1/2/3>, **discord.gg/rust** and \
+                    ||ptb.discord.com/channels/4/5/6||. also https://example.com";
+        assert_eq!(
+            extract_links(text).collect::<Vec<_>>(),
+            vec![
+                DiscordLink::Message {
+                    guild: 1,
+                    channel: 2,
+                    message: 3,
+                },
+                DiscordLink::Invite {
+                    code: "rust".to_string(),
+                },
+                DiscordLink::Message {
+                    guild: 4,
+                    channel: 5,
+                    message: 6,
+                },
+            ]
+        );
+        assert_eq!(find_guild_ids(text).collect::<Vec<_>>(), vec![1, 4]);
+    }
+
+    #[test]
+    fn ignores_trailing_punctuation() {
+        for text in [
+            "https://discord.com/channels/1/2/3.",
+            "https://discord.com/channels/1/2/3,",
+            "https://discord.com/channels/1/2/3!",
+            "https://discord.com/channels/1/2/3?",
+            "(https://discord.com/channels/1/2/3)",
+        ] {
+            assert_eq!(
+                extract_links(text).collect::<Vec<_>>(),
+                message(1, 2, 3).into_iter().collect::<Vec<_>>(),
+                "{text}"
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(link in any::<String>()) {
+            let _ = DiscordLink::parse(&link);
+        }
+
+        #[test]
+        fn extract_links_never_panics(text in any::<String>()) {
+            let _ = extract_links(&text).count();
+        }
+
+        #[test]
+        fn extract_links_never_panics_on_discord_like_text(
+            text in "[ <>()\\[\\]*|_~`'\"./:@a-z0-9]{0,40}(discord(app)?\\.(com|gg)[./@a-z0-9]{0,40}){0,3}",
+        ) {
+            let _ = find_guild_ids(&text).count();
+        }
+
+        #[test]
+        fn parses_any_message_link(
+            guild in 1u64..,
+            channel in 1u64..,
+            message in 1u64..,
+            subdomain in "(|www\\.|ptb\\.|canary\\.)",
+            wrapping in prop::sample::select(vec![("", ""), ("<", ">"), ("**", "**"), ("||", "||")]),
+        ) {
+            let link = format!(
+                "{}https://{subdomain}discord.com/channels/{guild}/{channel}/{message}{}",
+                wrapping.0, wrapping.1,
+            );
+            prop_assert_eq!(
+                DiscordLink::parse(&link),
+                Some(DiscordLink::Message { guild, channel, message })
+            );
+        }
+    }
+}
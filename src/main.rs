@@ -1,14 +1,50 @@
-use std::path::PathBuf;
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{BufRead, Write},
+    net::SocketAddr,
+    path::PathBuf,
+    process::ExitCode,
+    sync::Arc,
+};
 
-use clap::{Parser, Subcommand};
-use guildly::{GuildEntry, GuildlyHandler, commands, database::Database};
-use serenity::prelude::*;
+use clap::{Parser, Subcommand, ValueEnum};
+use guildly::{
+    GuildEntry, GuildlyHandler, LookupMiss, commands,
+    config::Config,
+    database::Database,
+    link::find_guild_ids,
+    sync::{self, Scope},
+    validation::{
+        EntryInput, PatchInput, parse_banner_url, parse_guild_id, parse_icon_url, parse_invite_url,
+    },
+    webhook::{self, Verifier},
+};
+use serde::Serialize;
+use serenity::{
+    all::{CreateCommand, Http, Timestamp},
+    prelude::*,
+};
+use tokio::net::TcpListener;
+use url::Url;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
-    #[arg(short, long, value_name = "FILE")]
-    database: PathBuf,
+    /// Config file, defaults to `guildly.toml` if present
+    #[arg(short, long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    #[arg(short, long, global = true, value_name = "FILE")]
+    database: Option<PathBuf>,
+
+    /// Log level or filter, such as `debug` or `info,serenity=warn`
+    #[arg(long, global = true, value_name = "FILTER")]
+    log_level: Option<String>,
+
+    /// Test guild for the commands, which are then only offered there
+    #[arg(long, global = true, value_name = "GUILD_ID")]
+    dev_guild: Option<u64>,
 
     #[command(subcommand)]
     command: Commands,
@@ -25,18 +61,443 @@ enum Commands {
         file: PathBuf,
     },
     Run {
+        /// Bot token; prefer `GUILDLY_TOKEN` or the config file, which stay out of shell history
+        #[arg(short, long)]
+        token: Option<String>,
+        /// Channel where suggestions are posted for review
+        #[arg(long, value_name = "CHANNEL_ID")]
+        maintainer_channel: Option<u64>,
+    },
+    /// Answers interactions that Discord posts to an HTTP endpoint, instead of connecting to the
+    /// gateway. Set the endpoint as the Interactions Endpoint URL, behind a TLS proxy.
+    ServeInteractions {
+        #[arg(short, long)]
+        token: Option<String>,
+        #[arg(long, value_name = "CHANNEL_ID")]
+        maintainer_channel: Option<u64>,
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+    },
+    Get {
+        id: u64,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    Search {
+        name: String,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Finds the servers linked from a text, or from a file if the argument is a path
+    Resolve {
+        input: String,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    Add {
+        #[arg(value_parser = parse_guild_id)]
+        id: u64,
         #[arg(short, long)]
-        token: String,
+        name: String,
+        #[arg(long, value_parser = parse_icon_url)]
+        icon: Option<Url>,
+        #[arg(long, value_parser = parse_invite_url)]
+        invite: Option<Url>,
+        #[arg(long)]
+        description: Option<String>,
+        /// The main language spoken in the server, e.g. `ja`
+        #[arg(long)]
+        language: Option<String>,
+        /// Comma separated tags
+        #[arg(long)]
+        tags: Option<String>,
+        /// Marks the server as age-restricted
+        #[arg(long)]
+        nsfw: bool,
+        #[arg(long, value_parser = parse_banner_url)]
+        banner: Option<Url>,
+        /// Replaces the entry if the server is already listed
+        #[arg(long)]
+        overwrite: bool,
+        /// Skips the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    Edit {
+        #[arg(value_parser = parse_guild_id)]
+        id: u64,
+        #[arg(short, long)]
+        name: Option<String>,
+        #[arg(long, value_parser = parse_icon_url)]
+        icon: Option<Url>,
+        #[arg(long, value_parser = parse_invite_url)]
+        invite: Option<Url>,
+        #[arg(long)]
+        description: Option<String>,
+        /// The main language spoken in the server, e.g. `ja`
+        #[arg(long)]
+        language: Option<String>,
+        /// Comma separated tags, replacing the current ones
+        #[arg(long)]
+        tags: Option<String>,
+        /// Whether the server is age-restricted
+        #[arg(long)]
+        nsfw: Option<bool>,
+        #[arg(long, value_parser = parse_banner_url)]
+        banner: Option<Url>,
+        #[arg(long, conflicts_with = "icon")]
+        clear_icon: bool,
+        #[arg(long, conflicts_with = "invite")]
+        clear_invite: bool,
+        #[arg(long, conflicts_with = "description")]
+        clear_description: bool,
+        #[arg(long, conflicts_with = "language")]
+        clear_language: bool,
+        #[arg(long, conflicts_with = "tags")]
+        clear_tags: bool,
+        #[arg(long, conflicts_with = "banner")]
+        clear_banner: bool,
+        /// Skips the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Lists the most looked up servers that are not in the directory
+    Wanted {
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    Remove {
+        #[arg(value_parser = parse_guild_id)]
+        id: u64,
+        /// Skips the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Manages the commands registered with Discord
+    #[command(name = "commands")]
+    Registry {
+        #[command(subcommand)]
+        command: RegistryCommands,
     },
 }
 
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Validates the effective config and prints it with secrets redacted
+    Check,
+}
+
+#[derive(Subcommand)]
+enum RegistryCommands {
+    /// Registers the enabled commands and deletes stale ones
+    Sync {
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Lists the registered commands and whether they match the local definitions
+    List {
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Deletes every registered command
+    Clear {
+        /// Skips the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct RegisteredCommand {
+    name: String,
+    id: Option<u64>,
+    status: &'static str,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut stdout = std::io::stdout().lock();
+    let header = header.map(String::from);
+
+    let mut widths = [0; N];
+    for row in std::iter::once(&header).chain(rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(stdout, "{}", line.trim_end()).unwrap();
+    }
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value).unwrap();
+    writeln!(stdout).unwrap();
+}
+
+fn print_entries(entries: &[GuildEntry], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => print_json(entries),
+        OutputFormat::Table => {
+            let rows = entries
+                .iter()
+                .map(|entry| {
+                    [
+                        entry.guild_id.to_string(),
+                        entry.name.clone(),
+                        entry
+                            .invite_url
+                            .as_ref()
+                            .map_or_else(String::new, |u| u.to_string()),
+                        entry
+                            .icon_url
+                            .as_ref()
+                            .map_or_else(String::new, |u| u.to_string()),
+                        entry.language.clone().unwrap_or_default(),
+                        entry.tags.join(","),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            print_table(["ID", "NAME", "INVITE", "ICON", "LANGUAGE", "TAGS"], &rows);
+        }
+    }
+}
+
+fn print_misses(misses: &[LookupMiss], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => print_json(misses),
+        OutputFormat::Table => {
+            let rows = misses
+                .iter()
+                .map(|miss| {
+                    [
+                        miss.guild_id.to_string(),
+                        miss.lookups.to_string(),
+                        Timestamp::from_unix_timestamp(miss.last_seen)
+                            .map_or_else(|_| miss.last_seen.to_string(), |t| t.to_string()),
+                        miss.message_links.join(" "),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            print_table(["ID", "LOOKUPS", "LAST SEEN", "MESSAGES"], &rows);
+        }
+    }
+}
+
+fn print_change(before: Option<&GuildEntry>, after: Option<&GuildEntry>, format: OutputFormat) {
+    match format {
+        OutputFormat::Json => print_json(&serde_json::json!({ "before": before, "after": after })),
+        OutputFormat::Table => {
+            for (label, entry) in [("Before", before), ("After", after)] {
+                println!("{label}:");
+                match entry {
+                    Some(entry) => print_entries(std::slice::from_ref(entry), format),
+                    None => println!("(none)"),
+                }
+            }
+        }
+    }
+}
+
+fn confirm(prompt: &str) -> bool {
+    eprint!("{prompt} [y/N] ");
+    std::io::stderr().flush().unwrap();
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).unwrap();
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Turns `err` into the message `main` prints before exiting, after everything, including the
+/// database, has been dropped.
+fn fail(err: impl fmt::Display) -> String {
+    err.to_string()
+}
+
+fn print_report(report: &sync::SyncReport, format: OutputFormat) {
+    match format {
+        OutputFormat::Json => print_json(report),
+        OutputFormat::Table => {
+            let rows = [
+                ("created", &report.created),
+                ("updated", &report.updated),
+                ("deleted", &report.deleted),
+                ("unchanged", &report.unchanged),
+            ]
+            .into_iter()
+            .flat_map(|(status, names)| names.iter().map(|name| [name.clone(), status.into()]))
+            .collect::<Vec<_>>();
+            print_table(["NAME", "STATUS"], &rows);
+        }
+    }
+}
+
+fn print_registered(commands: &[RegisteredCommand], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => print_json(commands),
+        OutputFormat::Table => {
+            let rows = commands
+                .iter()
+                .map(|command| {
+                    [
+                        command.name.clone(),
+                        command.id.map_or_else(String::new, |id| id.to_string()),
+                        command.status.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            print_table(["NAME", "ID", "STATUS"], &rows);
+        }
+    }
+}
+
+/// The definitions of the commands enabled by `config`.
+fn enabled_definitions(config: &Config) -> Vec<CreateCommand> {
+    let mut commands = commands::builtin_commands();
+    commands.retain(|command| config.is_enabled(command.name()));
+    commands.sort_by_key(|command| command.name());
+    commands
+        .iter()
+        .map(|command| command.definition())
+        .collect()
+}
+
+async fn manage_registry(config: &Config, command: RegistryCommands) -> Result<(), String> {
+    config.validate_registry().map_err(fail)?;
+    let http = Http::new(config.token().unwrap());
+    let application = http.get_current_application_info().await.map_err(fail)?;
+    http.set_application_id(application.id);
+    let scope = Scope::from_dev_guild(config.dev_guild);
+
+    match command {
+        RegistryCommands::Sync { format } => {
+            let report = sync::sync(&http, scope, enabled_definitions(config))
+                .await
+                .map_err(fail)?;
+            print_report(&report, format);
+        }
+        RegistryCommands::List { format } => {
+            let registered = scope.fetch(&http).await.map_err(fail)?;
+            let report = sync::diff(&enabled_definitions(config), &registered);
+            let status = |name: &str| {
+                if report.updated.iter().any(|updated| updated == name) {
+                    "outdated"
+                } else if report.deleted.iter().any(|deleted| deleted == name) {
+                    "stale"
+                } else {
+                    "up to date"
+                }
+            };
+            let mut commands = registered
+                .iter()
+                .map(|command| RegisteredCommand {
+                    name: command.name.clone(),
+                    id: Some(command.id.get()),
+                    status: status(&command.name),
+                })
+                .chain(report.created.iter().map(|name| RegisteredCommand {
+                    name: name.clone(),
+                    id: None,
+                    status: "not registered",
+                }))
+                .collect::<Vec<_>>();
+            commands.sort_by(|a, b| a.name.cmp(&b.name));
+            print_registered(&commands, format);
+        }
+        RegistryCommands::Clear { yes } => {
+            if !yes && !confirm("Delete every registered command?") {
+                return Err(fail("Aborted"));
+            }
+            scope.overwrite(&http, Vec::new()).await.map_err(fail)?;
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
-async fn main() {
-    let args = Args::parse();
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let database = Database::open(args.database).unwrap();
+async fn run(args: Args) -> Result<(), String> {
+    let mut overrides = Config {
+        database: args.database,
+        log_level: args.log_level,
+        dev_guild: args.dev_guild,
+        ..Config::default()
+    };
+    if let Commands::Run {
+        token,
+        maintainer_channel,
+    }
+    | Commands::ServeInteractions {
+        token,
+        maintainer_channel,
+        ..
+    } = &args.command
+    {
+        overrides.token = token.clone();
+        overrides.maintainer_channel = *maintainer_channel;
+    }
+    let config = Config::load(args.config.as_deref())
+        .map_err(fail)?
+        .merge(overrides);
+
+    let log_filter = config.log_filter().map_err(fail)?;
+    tracing_subscriber::fmt()
+        .with_env_filter(log_filter)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let command = match args.command {
+        Commands::Config {
+            command: ConfigCommands::Check,
+        } => {
+            config.validate().map_err(fail)?;
+            print!("{}", config.redacted().to_toml());
+            return Ok(());
+        }
+        Commands::Registry { command } => return manage_registry(&config, command).await,
+        command => command,
+    };
 
-    match args.command {
+    let database = config
+        .database()
+        .map(Database::open)
+        .map_err(fail)?
+        .map_err(fail)?;
+
+    match command {
         Commands::Export { file } => {
             let entries = database.export().unwrap();
             serde_json::to_writer_pretty(std::fs::File::create(file).unwrap(), &entries).unwrap();
@@ -46,25 +507,175 @@ async fn main() {
                 serde_json::from_reader(std::fs::File::open(file).unwrap()).unwrap();
             database.import(&entries).unwrap();
         }
-        Commands::Run { token } => {
-            let intents = GatewayIntents::GUILD_MESSAGES
-                | GatewayIntents::DIRECT_MESSAGES
-                | GatewayIntents::MESSAGE_CONTENT;
+        Commands::Get { id, format } => {
+            let entries = database.get(id).unwrap().into_iter().collect::<Vec<_>>();
+            print_entries(&entries, format);
+        }
+        Commands::Search { name, format } => {
+            let entries = database.search(&name).unwrap();
+            print_entries(&entries, format);
+        }
+        Commands::Resolve { input, format } => {
+            let text = if std::path::Path::new(&input).is_file() {
+                std::fs::read_to_string(&input).unwrap()
+            } else {
+                input
+            };
+            let mut seen = HashSet::new();
+            let mut entries = Vec::new();
+            for guild_id in find_guild_ids(&text).filter(|&guild_id| seen.insert(guild_id)) {
+                if let Some(entry) = database.get(guild_id).unwrap() {
+                    entries.push(entry);
+                }
+            }
+            print_entries(&entries, format);
+        }
+        Commands::Add {
+            id,
+            name,
+            icon,
+            invite,
+            description,
+            language,
+            tags,
+            nsfw,
+            banner,
+            overwrite,
+            yes,
+            format,
+        } => {
+            let input = EntryInput {
+                guild_id: Some(id),
+                name: Some(&name),
+                icon_url: icon,
+                invite_url: invite,
+                description: description.as_deref(),
+                language: language.as_deref(),
+                tags: tags.as_deref(),
+                nsfw,
+                banner_url: banner,
+            };
+            let after = input.validate().map_err(fail)?;
+            let before = database.get(after.guild_id).unwrap();
+            if before.is_some() && !overwrite {
+                return Err(fail(
+                    "Server already exists, use `edit` or pass `--overwrite`",
+                ));
+            }
+
+            print_change(before.as_ref(), Some(&after), format);
+            if !yes && !confirm("Add this server?") {
+                return Err(fail("Aborted"));
+            }
+            database.insert(&after).unwrap();
+        }
+        Commands::Edit {
+            id: guild_id,
+            name,
+            icon,
+            invite,
+            description,
+            language,
+            tags,
+            nsfw,
+            banner,
+            clear_icon,
+            clear_invite,
+            clear_description,
+            clear_language,
+            clear_tags,
+            clear_banner,
+            yes,
+            format,
+        } => {
+            let input = PatchInput {
+                name: name.as_deref(),
+                icon_url: icon,
+                invite_url: invite,
+                description: description.as_deref(),
+                language: language.as_deref(),
+                tags: tags.as_deref(),
+                nsfw,
+                banner_url: banner,
+                clear_icon,
+                clear_invite,
+                clear_description,
+                clear_language,
+                clear_tags,
+                clear_banner,
+            };
+            let patch = input.validate().map_err(fail)?;
+            let Some(before) = database.get(guild_id).unwrap() else {
+                return Err(fail("Not Found Guild"));
+            };
+            let after = patch.apply(&before);
 
-            let mut handler = GuildlyHandler::new(database);
+            print_change(Some(&before), Some(&after), format);
+            if !yes && !confirm("Apply these changes?") {
+                return Err(fail("Aborted"));
+            }
+            database.update(guild_id, &patch).unwrap();
+        }
+        Commands::Wanted { limit, format } => {
+            let misses = database.wanted(limit).unwrap();
+            print_misses(&misses, format);
+        }
+        Commands::Remove {
+            id: guild_id,
+            yes,
+            format,
+        } => {
+            let Some(before) = database.get(guild_id).unwrap() else {
+                return Err(fail("Not Found Guild"));
+            };
 
-            handler.register(Box::new(commands::add::AddServer));
-            handler.register(Box::new(commands::search::SearchServer));
-            handler.register(Box::new(commands::show_menu::ShowServersMenu));
+            print_change(Some(&before), None, format);
+            if !yes && !confirm("Remove this server?") {
+                return Err(fail("Aborted"));
+            }
+            database.remove(guild_id).unwrap();
+        }
+        Commands::Config { .. } | Commands::Registry { .. } => unreachable!(),
+        Commands::Run { .. } => {
+            let token = config.token().map_err(fail)?;
+            let intents = config.intents().map_err(fail)?;
+            config.validate().map_err(fail)?;
+            let handler = GuildlyHandler::from_config(database, &config);
 
-            let mut client = Client::builder(&token, intents)
+            let mut client = Client::builder(token, intents)
                 .event_handler(handler)
                 .await
                 .expect("Err creating client");
 
             if let Err(why) = client.start().await {
-                println!("Client error: {why:?}");
+                tracing::error!("Client error: {why:?}");
+            }
+        }
+        Commands::ServeInteractions { listen, .. } => {
+            config.validate().map_err(fail)?;
+            let http = Arc::new(Http::new(config.token().unwrap()));
+            let application = http.get_current_application_info().await.map_err(fail)?;
+            http.set_application_id(application.id);
+            let verifier = Verifier::from_hex(&application.verify_key).map_err(fail)?;
+
+            let handler = GuildlyHandler::from_config(database, &config);
+            match handler.sync_commands(&http).await {
+                Ok(report) => tracing::info!(
+                    "Synced commands: created {:?}, updated {:?}, deleted {:?}",
+                    report.created,
+                    report.updated,
+                    report.deleted
+                ),
+                Err(why) => tracing::error!("Failed to sync commands: {why:?}"),
+            }
+
+            let listener = TcpListener::bind(listen).await.map_err(fail)?;
+            tracing::info!("Listening for interactions on {listen}");
+            let router = webhook::router(Arc::new(handler), http, verifier);
+            if let Err(why) = webhook::serve(listener, router).await {
+                tracing::error!("Server error: {why:?}");
             }
         }
     }
+    Ok(())
 }
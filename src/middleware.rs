@@ -0,0 +1,143 @@
+use std::{sync::Arc, time::Instant};
+
+use serenity::{
+    all::{CommandInteraction, CreateEmbed, Http},
+    async_trait,
+};
+
+use crate::{
+    Color, GuildlyHandler,
+    commands::GuildlyCommand,
+    i18n::Locale,
+    response::{Reply, Response},
+    tr,
+};
+
+/// Wraps the execution of commands, e.g. to check permissions, limit usage or record metrics.
+///
+/// Implement [`before`](Middleware::before) and [`after`](Middleware::after) for simple hooks,
+/// or override [`around`](Middleware::around) to control whether and how the rest of the chain
+/// runs.
+#[async_trait]
+pub trait Middleware: Sync + Send {
+    /// Runs before the command. Returning a response skips the command and the middleware after
+    /// this one.
+    async fn before(
+        &self,
+        _handler: &GuildlyHandler,
+        _http: &Http,
+        _interaction: &CommandInteraction,
+        _command: &dyn GuildlyCommand,
+    ) -> Option<Response> {
+        None
+    }
+
+    /// Runs after the command, or after a later middleware skipped it, and may replace its
+    /// response.
+    async fn after(
+        &self,
+        _handler: &GuildlyHandler,
+        _http: &Http,
+        _interaction: &CommandInteraction,
+        _command: &dyn GuildlyCommand,
+        response: Response,
+    ) -> Response {
+        response
+    }
+
+    async fn around(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+        next: Next<'_>,
+    ) -> Response {
+        if let Some(response) = self.before(handler, http, interaction, next.command).await {
+            return response;
+        }
+        let response = next.run(handler, http, interaction).await;
+        self.after(handler, http, interaction, next.command, response)
+            .await
+    }
+}
+
+/// The rest of a middleware chain, ending with the command itself.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    pub command: &'a dyn GuildlyCommand,
+    middleware: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub fn new(command: &'a dyn GuildlyCommand, middleware: &'a [Arc<dyn Middleware>]) -> Self {
+        Self {
+            command,
+            middleware,
+        }
+    }
+
+    pub async fn run(
+        self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+    ) -> Response {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                first
+                    .around(handler, http, interaction, Next::new(self.command, rest))
+                    .await
+            }
+            None => self.command.execute(handler, http, interaction).await,
+        }
+    }
+}
+
+/// Logs every command run, with how long it took.
+pub struct Logging;
+
+#[async_trait]
+impl Middleware for Logging {
+    async fn around(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+        next: Next<'_>,
+    ) -> Response {
+        let started = Instant::now();
+        let response = next.run(handler, http, interaction).await;
+        tracing::debug!(
+            "{} ran {} in {:?}",
+            interaction.user.id,
+            next.command.name(),
+            started.elapsed()
+        );
+        response
+    }
+}
+
+/// Only lets [curators](GuildlyHandler::is_curator) run a command.
+pub struct CuratorsOnly;
+
+#[async_trait]
+impl Middleware for CuratorsOnly {
+    async fn before(
+        &self,
+        handler: &GuildlyHandler,
+        http: &Http,
+        interaction: &CommandInteraction,
+        _command: &dyn GuildlyCommand,
+    ) -> Option<Response> {
+        let member = interaction.member.as_deref();
+        if handler.is_curator(http, interaction.guild_id, member).await {
+            return None;
+        }
+        let locale = Locale::of(interaction);
+        let embed = CreateEmbed::new()
+            .color(Color::ERROR)
+            .title(tr!(locale, "curator.denied"))
+            .description(tr!(locale, "curator.denied_hint"));
+        Some(Reply::new().embed(embed).ephemeral(true).into())
+    }
+}
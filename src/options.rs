@@ -0,0 +1,241 @@
+use std::fmt;
+
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommandOption, CreateEmbed, ResolvedOption,
+    ResolvedValue,
+};
+use url::Url;
+
+use crate::{
+    Color,
+    i18n::{self, Locale},
+    tr,
+    validation::parse_guild_id,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionError {
+    Missing(&'static str),
+    Invalid {
+        name: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl OptionError {
+    pub fn title(&self, locale: Locale) -> &'static str {
+        match self {
+            Self::Missing(_) => tr!(locale, "option_error.missing_title"),
+            Self::Invalid { .. } => tr!(locale, "option_error.invalid_title"),
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        match self {
+            Self::Missing(name) => tr!(locale, "option_error.missing", name = name),
+            Self::Invalid { name, expected } => tr!(
+                locale,
+                "option_error.invalid",
+                name = name,
+                expected = i18n::text(locale, expected)
+            ),
+        }
+    }
+
+    pub fn create_embed(&self, locale: Locale) -> CreateEmbed {
+        CreateEmbed::new()
+            .color(Color::ERROR)
+            .title(self.title(locale))
+            .description(self.message(locale))
+    }
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(Locale::English))
+    }
+}
+
+impl std::error::Error for OptionError {}
+
+/// A value a command option can hold.
+pub trait OptionValue: Sized {
+    const KIND: CommandOptionType;
+    /// Catalog key describing valid values in error messages, e.g. "a URL".
+    const EXPECTED: &'static str;
+
+    fn from_resolved(value: &ResolvedValue) -> Option<Self>;
+}
+
+impl OptionValue for String {
+    const KIND: CommandOptionType = CommandOptionType::String;
+    const EXPECTED: &'static str = "expected.text";
+
+    fn from_resolved(value: &ResolvedValue) -> Option<Self> {
+        match value {
+            ResolvedValue::String(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl OptionValue for i64 {
+    const KIND: CommandOptionType = CommandOptionType::Integer;
+    const EXPECTED: &'static str = "expected.integer";
+
+    fn from_resolved(value: &ResolvedValue) -> Option<Self> {
+        match value {
+            ResolvedValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl OptionValue for bool {
+    const KIND: CommandOptionType = CommandOptionType::Boolean;
+    const EXPECTED: &'static str = "expected.boolean";
+
+    fn from_resolved(value: &ResolvedValue) -> Option<Self> {
+        match value {
+            ResolvedValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Server IDs are typed as text, since Discord clients cannot enter integers that large.
+impl OptionValue for u64 {
+    const KIND: CommandOptionType = CommandOptionType::String;
+    const EXPECTED: &'static str = "expected.guild_id";
+
+    fn from_resolved(value: &ResolvedValue) -> Option<Self> {
+        match value {
+            ResolvedValue::String(value) => parse_guild_id(value).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl OptionValue for Url {
+    const KIND: CommandOptionType = CommandOptionType::String;
+    const EXPECTED: &'static str = "expected.url";
+
+    fn from_resolved(value: &ResolvedValue) -> Option<Self> {
+        match value {
+            ResolvedValue::String(value) => Url::parse(value.trim()).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// The options of a command, declared once with [`command_options!`](crate::command_options).
+pub trait CommandOptions: Sized {
+    fn create_options() -> Vec<CreateCommandOption>;
+    fn parse(options: &[ResolvedOption]) -> Result<Self, OptionError>;
+
+    fn from_interaction(interaction: &CommandInteraction) -> Result<Self, OptionError> {
+        Self::parse(&interaction.data.options())
+    }
+}
+
+pub fn create_option<T: OptionValue>(
+    name: &'static str,
+    description: &'static str,
+    required: bool,
+) -> CreateCommandOption {
+    CreateCommandOption::new(T::KIND, name, description).required(required)
+}
+
+/// Applies the closure given to an option of [`command_options!`](crate::command_options).
+pub fn customize(
+    option: CreateCommandOption,
+    customize: impl FnOnce(CreateCommandOption) -> CreateCommandOption,
+) -> CreateCommandOption {
+    customize(option)
+}
+
+pub fn get<T: OptionValue>(
+    options: &[ResolvedOption],
+    name: &'static str,
+) -> Result<Option<T>, OptionError> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .map(|option| {
+            T::from_resolved(&option.value).ok_or(OptionError::Invalid {
+                name,
+                expected: T::EXPECTED,
+            })
+        })
+        .transpose()
+}
+
+pub fn get_required<T: OptionValue>(
+    options: &[ResolvedOption],
+    name: &'static str,
+) -> Result<T, OptionError> {
+    get(options, name)?.ok_or(OptionError::Missing(name))
+}
+
+/// Declares a struct of command options and implements [`CommandOptions`] for it. Each field
+/// names its option, description and whether it is `required` or `optional`, and may end with a
+/// closure customizing the [`CreateCommandOption`], e.g. to add choices.
+///
+/// ```
+/// guildly::command_options! {
+///     pub struct LookupOptions {
+///         guild_id: required u64 = "id", "Server Id";
+///         limit: optional i64 = "limit", "Number of results", |option| option.min_int_value(1);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! command_options {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $field:ident: $presence:ident $ty:ty = $option:literal, $description:literal
+                    $(, $customize:expr)?;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(pub $field: $crate::command_options!(@type $presence $ty),)*
+        }
+
+        impl $crate::options::CommandOptions for $name {
+            fn create_options() -> Vec<::serenity::all::CreateCommandOption> {
+                vec![$({
+                    let option = $crate::options::create_option::<$ty>(
+                        $option,
+                        $description,
+                        $crate::command_options!(@required $presence),
+                    );
+                    let option = $crate::i18n::localize_option(option, $option);
+                    $(let option = $crate::options::customize(option, $customize);)?
+                    option
+                }),*]
+            }
+
+            fn parse(
+                options: &[::serenity::all::ResolvedOption],
+            ) -> Result<Self, $crate::options::OptionError> {
+                Ok(Self {
+                    $($field: $crate::command_options!(@get $presence options $option),)*
+                })
+            }
+        }
+    };
+    (@type required $ty:ty) => { $ty };
+    (@type optional $ty:ty) => { Option<$ty> };
+    (@required required) => { true };
+    (@required optional) => { false };
+    (@get required $options:ident $option:literal) => {
+        $crate::options::get_required($options, $option)?
+    };
+    (@get optional $options:ident $option:literal) => {
+        $crate::options::get($options, $option)?
+    };
+}
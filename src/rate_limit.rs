@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    all::{
+        CommandInteraction, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage, Http,
+    },
+    async_trait,
+};
+
+use crate::{
+    Color, GuildlyHandler,
+    commands::GuildlyCommand,
+    i18n::Locale,
+    middleware::Middleware,
+    response::{Reply, Response},
+    tr,
+};
+
+/// Idle buckets are pruned once the limiter tracks more keys than this.
+const MAX_BUCKETS: usize = 10_000;
+/// Buckets unused for this long are full again, so they can be forgotten.
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// A token bucket: up to `capacity` uses in a burst, with one use regained every `refill_every`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub refill_every: Duration,
+}
+
+impl RateLimit {
+    pub const fn new(capacity: u32, refill_every: Duration) -> Self {
+        Self {
+            capacity,
+            refill_every,
+        }
+    }
+}
+
+/// The limits of a command, each tracked separately per command. `None` disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimits {
+    pub per_user: Option<RateLimit>,
+    pub per_guild: Option<RateLimit>,
+    pub per_command: Option<RateLimit>,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            per_user: Some(RateLimit::new(5, Duration::from_secs(2))),
+            per_guild: Some(RateLimit::new(20, Duration::from_millis(500))),
+            per_command: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BucketKey {
+    User(&'static str, u64),
+    Guild(&'static str, u64),
+    Command(&'static str),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated);
+        let refilled = elapsed.as_secs_f64() / limit.refill_every.as_secs_f64();
+        self.tokens = (self.tokens + refilled).min(limit.capacity as f64);
+        self.updated = now;
+    }
+
+    fn retry_after(&self, limit: &RateLimit) -> Duration {
+        limit.refill_every.mul_f64((1.0 - self.tokens).max(0.0))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes one use of `command` from every applicable bucket, or returns how long to wait if
+    /// any of them is empty. Nothing is taken when the use is refused.
+    pub fn check(
+        &self,
+        command: &'static str,
+        limits: &RateLimits,
+        user_id: u64,
+        guild_id: Option<u64>,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let keys = [
+            limits
+                .per_user
+                .map(|limit| (BucketKey::User(command, user_id), limit)),
+            limits
+                .per_guild
+                .zip(guild_id)
+                .map(|(limit, guild_id)| (BucketKey::Guild(command, guild_id), limit)),
+            limits
+                .per_command
+                .map(|limit| (BucketKey::Command(command), limit)),
+        ];
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_BUCKETS {
+            buckets.retain(|_, bucket| {
+                now.saturating_duration_since(bucket.updated) < IDLE_BUCKET_TTL
+            });
+        }
+
+        let mut retry_after = None;
+        for (key, limit) in keys.iter().flatten() {
+            let bucket = buckets.entry(*key).or_insert(Bucket {
+                tokens: limit.capacity as f64,
+                updated: now,
+            });
+            bucket.refill(limit, now);
+            if bucket.tokens < 1.0 {
+                retry_after = retry_after.max(Some(bucket.retry_after(limit)));
+            }
+        }
+        if let Some(retry_after) = retry_after {
+            return Err(retry_after);
+        }
+
+        for (key, _) in keys.iter().flatten() {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Middleware refusing commands used more often than their [`GuildlyCommand::rate_limits`].
+#[derive(Debug, Default)]
+pub struct RateLimiting {
+    limiter: RateLimiter,
+}
+
+impl RateLimiting {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimiting {
+    async fn before(
+        &self,
+        _handler: &GuildlyHandler,
+        _http: &Http,
+        interaction: &CommandInteraction,
+        command: &dyn GuildlyCommand,
+    ) -> Option<Response> {
+        self.limiter
+            .check(
+                command.name(),
+                &command.rate_limits(),
+                interaction.user.id.get(),
+                interaction.guild_id.map(|guild_id| guild_id.get()),
+                Instant::now(),
+            )
+            .err()
+            .map(|retry_after| create_rate_limited_response(retry_after, Locale::of(interaction)))
+    }
+}
+
+fn create_rate_limited_embed(retry_after: Duration, locale: Locale) -> CreateEmbed {
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0);
+    CreateEmbed::new()
+        .color(Color::WARN)
+        .title(tr!(locale, "rate_limit.title"))
+        .description(tr!(locale, "rate_limit.retry", seconds = seconds))
+}
+
+fn create_rate_limited_response(retry_after: Duration, locale: Locale) -> Response {
+    Reply::new()
+        .embed(create_rate_limited_embed(retry_after, locale))
+        .ephemeral(true)
+        .into()
+}
+
+/// The reply to a component or modal used more often than its limits allow.
+pub(crate) fn create_rate_limited_message(
+    retry_after: Duration,
+    locale: Locale,
+) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .add_embed(create_rate_limited_embed(retry_after, locale))
+            .ephemeral(true),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn per_user(capacity: u32, refill_every: Duration) -> RateLimits {
+        RateLimits {
+            per_user: Some(RateLimit::new(capacity, refill_every)),
+            per_guild: None,
+            per_command: None,
+        }
+    }
+
+    fn assert_close(actual: Duration, expected: Duration) {
+        let difference = actual.abs_diff(expected);
+        assert!(
+            difference < Duration::from_micros(1),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn allows_a_burst_up_to_capacity() {
+        let limiter = RateLimiter::new();
+        let limits = per_user(3, SECOND);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.check("search", &limits, 1, None, now), Ok(()));
+        }
+        assert!(limiter.check("search", &limits, 1, None, now).is_err());
+    }
+
+    #[test]
+    fn refuses_with_the_time_until_the_next_use() {
+        let limiter = RateLimiter::new();
+        let limits = per_user(1, SECOND);
+        let now = Instant::now();
+        limiter.check("search", &limits, 1, None, now).unwrap();
+
+        let retry_after = limiter.check("search", &limits, 1, None, now).unwrap_err();
+        assert_close(retry_after, SECOND);
+        let later = now + Duration::from_millis(250);
+        let retry_after = limiter
+            .check("search", &limits, 1, None, later)
+            .unwrap_err();
+        assert_close(retry_after, Duration::from_millis(750));
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = RateLimiter::new();
+        let limits = per_user(2, SECOND);
+        let now = Instant::now();
+        limiter.check("search", &limits, 1, None, now).unwrap();
+        limiter.check("search", &limits, 1, None, now).unwrap();
+
+        let later = now + SECOND;
+        assert_eq!(limiter.check("search", &limits, 1, None, later), Ok(()));
+        assert!(limiter.check("search", &limits, 1, None, later).is_err());
+
+        // Buckets never hold more than their capacity, however long they are idle.
+        let much_later = later + 10 * SECOND;
+        for _ in 0..2 {
+            assert_eq!(
+                limiter.check("search", &limits, 1, None, much_later),
+                Ok(())
+            );
+        }
+        assert!(
+            limiter
+                .check("search", &limits, 1, None, much_later)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn tracks_users_and_commands_separately() {
+        let limiter = RateLimiter::new();
+        let limits = per_user(1, SECOND);
+        let now = Instant::now();
+        limiter.check("search", &limits, 1, None, now).unwrap();
+
+        assert!(limiter.check("search", &limits, 1, None, now).is_err());
+        assert_eq!(limiter.check("search", &limits, 2, None, now), Ok(()));
+        assert_eq!(limiter.check("browse", &limits, 1, None, now), Ok(()));
+    }
+
+    #[test]
+    fn takes_nothing_when_refused() {
+        let limiter = RateLimiter::new();
+        let limits = RateLimits {
+            per_user: Some(RateLimit::new(1, 60 * SECOND)),
+            per_guild: Some(RateLimit::new(1, Duration::from_millis(10))),
+            per_command: None,
+        };
+        let now = Instant::now();
+        limiter.check("search", &limits, 1, Some(9), now).unwrap();
+
+        // The server's bucket is empty, so user 2 keeps their own token.
+        assert!(limiter.check("search", &limits, 2, Some(9), now).is_err());
+        let later = now + Duration::from_millis(10);
+        assert_eq!(limiter.check("search", &limits, 2, Some(9), later), Ok(()));
+        let retry_after = limiter
+            .check(
+                "search",
+                &limits,
+                2,
+                Some(9),
+                later + Duration::from_millis(10),
+            )
+            .unwrap_err();
+        assert!(retry_after > 50 * SECOND);
+    }
+}
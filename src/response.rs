@@ -0,0 +1,338 @@
+use std::time::Duration;
+
+use serenity::{
+    Result,
+    all::{
+        AuthorizingIntegrationOwner, CommandInteraction, CreateActionRow, CreateAttachment,
+        CreateEmbed, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateModal, EditInteractionResponse, Http,
+        InteractionContext,
+    },
+};
+
+use crate::{Color, i18n::Locale, tr};
+
+/// A message sent in reply to a command, followed by any number of follow-up messages.
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct Reply {
+    content: Option<String>,
+    embeds: Vec<CreateEmbed>,
+    components: Vec<CreateActionRow>,
+    attachments: Vec<CreateAttachment>,
+    ephemeral: bool,
+    follow_ups: Vec<Reply>,
+}
+
+impl Reply {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    pub fn embed(mut self, embed: CreateEmbed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    pub fn embeds(mut self, embeds: impl IntoIterator<Item = CreateEmbed>) -> Self {
+        self.embeds.extend(embeds);
+        self
+    }
+
+    pub fn components(mut self, components: Vec<CreateActionRow>) -> Self {
+        self.components.extend(components);
+        self
+    }
+
+    pub fn attachment(mut self, attachment: CreateAttachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Only shows the message to the user who ran the command.
+    pub fn ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    /// Sends `reply`, and then its own follow-ups, as separate messages after this one.
+    pub fn follow_up(mut self, mut reply: Reply) -> Self {
+        let follow_ups = std::mem::take(&mut reply.follow_ups);
+        self.follow_ups.push(reply);
+        self.follow_ups.extend(follow_ups);
+        self
+    }
+
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    /// Makes this message and its follow-ups ephemeral.
+    fn into_ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        for follow_up in &mut self.follow_ups {
+            follow_up.ephemeral = true;
+        }
+        self
+    }
+
+    fn into_message(self) -> (CreateInteractionResponseMessage, Vec<Reply>) {
+        let mut message = CreateInteractionResponseMessage::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .files(self.attachments)
+            .ephemeral(self.ephemeral);
+        if let Some(content) = self.content {
+            message = message.content(content);
+        }
+        (message, self.follow_ups)
+    }
+
+    fn into_edit(self) -> (EditInteractionResponse, Vec<Reply>) {
+        let mut edit = EditInteractionResponse::new()
+            .embeds(self.embeds)
+            .components(self.components);
+        for attachment in self.attachments {
+            edit = edit.new_attachment(attachment);
+        }
+        if let Some(content) = self.content {
+            edit = edit.content(content);
+        }
+        (edit, self.follow_ups)
+    }
+
+    fn into_follow_up(self) -> CreateInteractionResponseFollowup {
+        let mut follow_up = CreateInteractionResponseFollowup::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .files(self.attachments)
+            .ephemeral(self.ephemeral);
+        if let Some(content) = self.content {
+            follow_up = follow_up.content(content);
+        }
+        follow_up
+    }
+}
+
+impl From<CreateEmbed> for Reply {
+    fn from(embed: CreateEmbed) -> Self {
+        Self::new().embed(embed)
+    }
+}
+
+/// What a command responds with.
+#[derive(Debug, Clone)]
+#[must_use]
+pub enum Response {
+    Reply(Reply),
+    /// Opens a form. Only possible as the first response to an interaction.
+    Modal(CreateModal),
+}
+
+impl Response {
+    /// Makes the reply ephemeral, along with its follow-ups.
+    pub fn into_ephemeral(self) -> Self {
+        match self {
+            Self::Reply(reply) => Self::Reply(reply.into_ephemeral()),
+            modal => modal,
+        }
+    }
+
+    /// Whether the response uploads files, which can only be sent by a request of its own rather
+    /// than in the body of an HTTP interaction reply.
+    pub fn has_attachments(&self) -> bool {
+        match self {
+            Self::Reply(reply) => !reply.attachments.is_empty(),
+            Self::Modal(_) => false,
+        }
+    }
+
+    /// Splits the response into the initial interaction response and the follow-ups to send
+    /// after it.
+    pub fn into_interaction_response(self) -> (CreateInteractionResponse, Vec<Reply>) {
+        match self {
+            Self::Reply(reply) => {
+                let (message, follow_ups) = reply.into_message();
+                (CreateInteractionResponse::Message(message), follow_ups)
+            }
+            Self::Modal(modal) => (CreateInteractionResponse::Modal(modal), Vec::new()),
+        }
+    }
+
+    /// Sends the response to `interaction`. When the interaction has been `deferred`, the reply
+    /// replaces the "thinking" message instead.
+    pub async fn send(
+        self,
+        http: &Http,
+        interaction: &CommandInteraction,
+        deferred: bool,
+    ) -> Result<()> {
+        let follow_ups = match (self, deferred) {
+            (response, false) => {
+                let (initial, follow_ups) = response.into_interaction_response();
+                interaction.create_response(http, initial).await?;
+                follow_ups
+            }
+            (Self::Reply(reply), true) => {
+                let (edit, follow_ups) = reply.into_edit();
+                interaction.edit_response(http, edit).await?;
+                follow_ups
+            }
+            (Self::Modal(_), true) => {
+                // Forms can only be the first response, so tell the user instead of leaving them
+                // with the "thinking" message.
+                tracing::warn!(
+                    "Cannot open a modal after deferring {}",
+                    interaction.data.name
+                );
+                let locale = Locale::of(interaction);
+                let embed = CreateEmbed::new()
+                    .color(Color::ERROR)
+                    .title(tr!(locale, "response.form_unavailable"));
+                interaction
+                    .edit_response(http, EditInteractionResponse::new().embed(embed))
+                    .await?;
+                return Ok(());
+            }
+        };
+        send_follow_ups(http, interaction, follow_ups).await
+    }
+}
+
+pub async fn send_follow_ups(
+    http: &Http,
+    interaction: &CommandInteraction,
+    follow_ups: Vec<Reply>,
+) -> Result<()> {
+    for reply in follow_ups {
+        interaction
+            .create_followup(http, reply.into_follow_up())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Whether `interaction` comes from a user install in a server or group DM the bot is not in.
+/// The bot cannot read such channels, and only ephemeral replies are sent there.
+pub fn bot_is_absent(interaction: &CommandInteraction) -> bool {
+    let owners = &interaction.authorizing_integration_owners.0;
+    interaction.context != Some(InteractionContext::BotDm)
+        && !owners.is_empty()
+        && !owners
+            .iter()
+            .any(|owner| matches!(owner, AuthorizingIntegrationOwner::GuildInstall(_)))
+}
+
+/// How long a command may run before its interaction is deferred, leaving a margin before
+/// Discord's 3 second deadline.
+pub const DEFER_AFTER: Duration = Duration::from_secs(2);
+
+/// Runs `execute` to completion. If it is still running after `budget`, or as soon as it waits
+/// on anything when `slow` is set, `defer` is awaited first so that Discord does not time out
+/// the interaction. Returns the response and whether the interaction was deferred.
+///
+/// The budget is measured with [`tokio::time`], so it follows a paused test clock.
+pub async fn execute_with_deferral(
+    execute: impl Future<Output = Response>,
+    slow: bool,
+    budget: Duration,
+    defer: impl Future<Output = Result<()>>,
+) -> (Response, bool) {
+    let budget = if slow { Duration::ZERO } else { budget };
+    let mut execute = std::pin::pin!(execute);
+    tokio::select! {
+        biased;
+        response = &mut execute => return (response, false),
+        () = tokio::time::sleep(budget) => {}
+    }
+
+    if let Err(why) = defer.await {
+        tracing::warn!("Failed to defer an interaction: {why:?}");
+    }
+    (execute.await, true)
+}
+
+/// Acknowledges `interaction`, showing a "thinking" message until [`Response::send`] is called
+/// with `deferred` set.
+pub async fn defer(http: &Http, interaction: &CommandInteraction, ephemeral: bool) -> Result<()> {
+    interaction
+        .create_response(http, create_defer(ephemeral))
+        .await
+}
+
+pub fn create_defer(ephemeral: bool) -> CreateInteractionResponse {
+    CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(ephemeral))
+}
+
+impl From<Reply> for Response {
+    fn from(reply: Reply) -> Self {
+        Self::Reply(reply)
+    }
+}
+
+impl From<CreateEmbed> for Response {
+    fn from(embed: CreateEmbed) -> Self {
+        Self::Reply(embed.into())
+    }
+}
+
+impl From<CreateModal> for Response {
+    fn from(modal: CreateModal) -> Self {
+        Self::Modal(modal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use tokio::time::{Instant, sleep};
+
+    use super::*;
+
+    /// Runs a command that takes `duration`, returning whether it was deferred and, if so, how
+    /// long after the start.
+    async fn run(duration: Duration, slow: bool) -> (bool, Option<Duration>) {
+        let start = Instant::now();
+        let deferred_at = Cell::new(None);
+        let execute = async {
+            if !duration.is_zero() {
+                sleep(duration).await;
+            }
+            Response::from(Reply::new())
+        };
+        let defer = async {
+            deferred_at.set(Some(start.elapsed()));
+            Ok(())
+        };
+        let (_, deferred) = execute_with_deferral(execute, slow, DEFER_AFTER, defer).await;
+        (deferred, deferred_at.get())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_defer_fast_commands() {
+        assert_eq!(run(Duration::from_secs(1), false).await, (false, None));
+        assert_eq!(run(Duration::ZERO, true).await, (false, None));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn defers_commands_after_the_budget() {
+        assert_eq!(
+            run(Duration::from_secs(5), false).await,
+            (true, Some(DEFER_AFTER))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn defers_slow_commands_when_they_wait() {
+        assert_eq!(
+            run(Duration::from_millis(100), true).await,
+            (true, Some(Duration::ZERO))
+        );
+    }
+}
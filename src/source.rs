@@ -0,0 +1,148 @@
+use std::fmt;
+
+use serde::Deserialize;
+use serenity::{
+    all::{Attachment, Embed, LightMethod, Message, MessageReferenceKind, Request, Route},
+    http::Http,
+};
+
+use crate::{i18n::Locale, tr};
+
+/// Attachments larger than this are not downloaded when looking for links.
+const MAX_ATTACHMENT_SIZE: u32 = 64 * 1024;
+
+/// The part of a message a link was found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkSource {
+    Content,
+    Embed,
+    Forwarded,
+    Attachment(String),
+}
+
+impl LinkSource {
+    /// Names the part of the message in `locale`.
+    pub fn describe(&self, locale: Locale) -> String {
+        match self {
+            Self::Content => tr!(locale, "source.content").to_string(),
+            Self::Embed => tr!(locale, "source.embed").to_string(),
+            Self::Forwarded => tr!(locale, "source.forwarded").to_string(),
+            Self::Attachment(filename) => tr!(locale, "source.attachment", filename = filename),
+        }
+    }
+}
+
+impl fmt::Display for LinkSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe(Locale::English))
+    }
+}
+
+/// The parts of a forwarded message kept in its snapshot, which serenity does not model yet.
+#[derive(Deserialize)]
+struct RawMessage {
+    #[serde(default)]
+    message_snapshots: Vec<RawSnapshot>,
+}
+
+#[derive(Deserialize)]
+struct RawSnapshot {
+    message: RawSnapshotMessage,
+}
+
+#[derive(Deserialize)]
+struct RawSnapshotMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    embeds: Vec<Embed>,
+}
+
+fn embed_texts(embed: &Embed) -> impl Iterator<Item = &str> {
+    [
+        embed.title.as_deref(),
+        embed.description.as_deref(),
+        embed.url.as_deref(),
+        embed.author.as_ref().map(|author| author.name.as_str()),
+        embed
+            .author
+            .as_ref()
+            .and_then(|author| author.url.as_deref()),
+        embed.footer.as_ref().map(|footer| footer.text.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(
+        embed
+            .fields
+            .iter()
+            .flat_map(|field| [field.name.as_str(), field.value.as_str()]),
+    )
+}
+
+fn is_text_attachment(attachment: &Attachment) -> bool {
+    attachment.size <= MAX_ATTACHMENT_SIZE
+        && (attachment
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("text/"))
+            || attachment.filename.ends_with(".txt"))
+}
+
+async fn forwarded_snapshots(http: Option<&Http>, message: &Message) -> Vec<RawSnapshot> {
+    let Some(http) = http else {
+        return Vec::new();
+    };
+    let is_forward = message
+        .message_reference
+        .as_ref()
+        .is_some_and(|reference| reference.kind == MessageReferenceKind::Forward);
+    if !is_forward {
+        return Vec::new();
+    }
+
+    let request = Request::new(
+        Route::ChannelMessage {
+            channel_id: message.channel_id,
+            message_id: message.id,
+        },
+        LightMethod::Get,
+    );
+    match http.fire::<RawMessage>(request).await {
+        Ok(raw) => raw.message_snapshots,
+        Err(why) => {
+            tracing::warn!("Failed to fetch forwarded message {}: {why:?}", message.id);
+            Vec::new()
+        }
+    }
+}
+
+/// Collects every text of `message` that may contain links: the content, embeds, the snapshots
+/// of a forwarded message and small text attachments. Forwarded messages are only fetched with
+/// `http`, which fails in channels the bot cannot see.
+pub async fn message_texts(http: Option<&Http>, message: &Message) -> Vec<(LinkSource, String)> {
+    let mut texts = vec![(LinkSource::Content, message.content.clone())];
+
+    for embed in &message.embeds {
+        texts.extend(embed_texts(embed).map(|text| (LinkSource::Embed, text.to_string())));
+    }
+
+    for snapshot in forwarded_snapshots(http, message).await {
+        texts.push((LinkSource::Forwarded, snapshot.message.content));
+        for embed in &snapshot.message.embeds {
+            texts.extend(embed_texts(embed).map(|text| (LinkSource::Forwarded, text.to_string())));
+        }
+    }
+
+    for attachment in message.attachments.iter().filter(|a| is_text_attachment(a)) {
+        match attachment.download().await {
+            Ok(bytes) => texts.push((
+                LinkSource::Attachment(attachment.filename.clone()),
+                String::from_utf8_lossy(&bytes).into_owned(),
+            )),
+            Err(why) => tracing::warn!("Failed to download {}: {why:?}", attachment.filename),
+        }
+    }
+
+    texts
+}
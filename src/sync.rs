@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use serenity::{
+    Result,
+    all::{Command, CreateCommand, GuildId, Http},
+};
+
+/// Keys compared even when a local definition leaves them out, so that removing an option or a
+/// description is noticed.
+const ALWAYS_COMPARED: [&str; 5] = [
+    "name",
+    "type",
+    "description",
+    "options",
+    "default_member_permissions",
+];
+
+/// Where commands are registered. Guild commands update instantly, global ones may take up to
+/// an hour to reach every client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Guild(GuildId),
+}
+
+impl Scope {
+    pub fn from_dev_guild(dev_guild: Option<u64>) -> Self {
+        dev_guild.map_or(Self::Global, |guild_id| Self::Guild(GuildId::new(guild_id)))
+    }
+
+    pub async fn fetch(self, http: &Http) -> Result<Vec<Command>> {
+        match self {
+            Self::Global => Command::get_global_commands_with_localizations(http).await,
+            Self::Guild(guild_id) => guild_id.get_commands_with_localizations(http).await,
+        }
+    }
+
+    /// Replaces every command of this scope with `definitions`.
+    pub async fn overwrite(
+        self,
+        http: &Http,
+        definitions: Vec<CreateCommand>,
+    ) -> Result<Vec<Command>> {
+        match self {
+            Self::Global => Command::set_global_commands(http, definitions).await,
+            Self::Guild(guild_id) => guild_id.set_commands(http, definitions).await,
+        }
+    }
+}
+
+/// The differences between local definitions and the commands registered on Discord.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn is_changed(&self) -> bool {
+        !(self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty())
+    }
+}
+
+/// Compares `definitions` with the `registered` commands, by name.
+pub fn diff(definitions: &[CreateCommand], registered: &[Command]) -> SyncReport {
+    let registered = registered
+        .iter()
+        .map(|command| (command.name.as_str(), normalize(to_value(command))))
+        .collect::<HashMap<_, _>>();
+
+    let mut report = SyncReport::default();
+    for definition in definitions {
+        let local = normalize(to_value(definition));
+        let name = local["name"].as_str().unwrap_or_default().to_string();
+        match registered.get(name.as_str()) {
+            None => report.created.push(name),
+            Some(remote) if is_same(&local, remote) => report.unchanged.push(name),
+            Some(_) => report.updated.push(name),
+        }
+    }
+    let local_names = report
+        .created
+        .iter()
+        .chain(&report.updated)
+        .chain(&report.unchanged)
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    report.deleted = registered
+        .into_keys()
+        .filter(|name| !local_names.contains(name))
+        .map(String::from)
+        .collect();
+
+    for names in [
+        &mut report.created,
+        &mut report.updated,
+        &mut report.deleted,
+        &mut report.unchanged,
+    ] {
+        names.sort();
+    }
+    report
+}
+
+/// Registers `definitions` in `scope`, with a single bulk overwrite that also deletes stale
+/// commands. Nothing is sent when Discord already has the same definitions.
+pub async fn sync(
+    http: &Http,
+    scope: Scope,
+    definitions: Vec<CreateCommand>,
+) -> Result<SyncReport> {
+    let registered = scope.fetch(http).await?;
+    let report = diff(&definitions, &registered);
+    if report.is_changed() {
+        scope.overwrite(http, definitions).await?;
+    }
+    Ok(report)
+}
+
+fn to_value(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+/// Whether a remote command matches a local definition on every key the definition sets.
+fn is_same(local: &Value, remote: &Value) -> bool {
+    let (Value::Object(local), Value::Object(remote)) = (local, remote) else {
+        return false;
+    };
+    let local_type = local.get("type").cloned().unwrap_or(Value::from(1));
+    if remote.get("type") != Some(&local_type) {
+        return false;
+    }
+    local
+        .keys()
+        .map(String::as_str)
+        .chain(ALWAYS_COMPARED)
+        .filter(|key| *key != "type")
+        .all(|key| local.get(key) == remote.get(key))
+}
+
+/// Drops defaults (`null`, `false`, empty strings, lists and maps), which Discord and the
+/// builders disagree on whether to send.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, normalize(value)))
+                .filter(|(_, value)| !is_default(value))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize).collect()),
+        value => value,
+    }
+}
+
+fn is_default(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(value) => value.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serenity::all::{
+        CommandOptionType, CreateCommandOption, InstallationContext, InteractionContext,
+        Permissions,
+    };
+
+    use super::*;
+
+    /// `/search` as Discord returns it, with the defaults it fills in.
+    fn registered() -> Value {
+        json!({
+            "id": "1",
+            "application_id": "2",
+            "version": "3",
+            "type": 1,
+            "name": "search",
+            "name_localizations": {"ja": "検索"},
+            "description": "Search servers",
+            "description_localizations": null,
+            "options": [{
+                "type": 3,
+                "name": "name",
+                "description": "Guild Name",
+                "required": false,
+            }],
+            "default_member_permissions": null,
+            "dm_permission": true,
+            "nsfw": false,
+            "integration_types": [0],
+            "contexts": null,
+        })
+    }
+
+    fn definition() -> CreateCommand {
+        CreateCommand::new("search")
+            .name_localized("ja", "検索")
+            .description("Search servers")
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Guild Name",
+            ))
+            .integration_types(vec![InstallationContext::Guild])
+    }
+
+    fn diff_one(definition: CreateCommand, registered: Value) -> SyncReport {
+        let registered = serde_json::from_value::<Command>(registered).unwrap();
+        diff(&[definition], &[registered])
+    }
+
+    fn updated() -> SyncReport {
+        SyncReport {
+            updated: vec!["search".to_string()],
+            ..SyncReport::default()
+        }
+    }
+
+    #[test]
+    fn keeps_identical_definitions() {
+        let report = diff_one(definition(), registered());
+        assert_eq!(report.unchanged, ["search"]);
+        assert!(!report.is_changed());
+    }
+
+    #[test]
+    fn notices_a_changed_description() {
+        let report = diff_one(definition().description("Find servers"), registered());
+        assert_eq!(report, updated());
+    }
+
+    #[test]
+    fn notices_added_and_removed_options() {
+        let added = definition().add_option(CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "id",
+            "Server Id",
+        ));
+        assert_eq!(diff_one(added, registered()), updated());
+
+        let removed = CreateCommand::new("search")
+            .name_localized("ja", "検索")
+            .description("Search servers")
+            .integration_types(vec![InstallationContext::Guild]);
+        assert_eq!(diff_one(removed, registered()), updated());
+    }
+
+    #[test]
+    fn notices_changed_localizations() {
+        let renamed = definition().name_localized("ja", "サーバー検索");
+        assert_eq!(diff_one(renamed, registered()), updated());
+
+        let described = definition().description_localized("ja", "サーバーを検索します");
+        assert_eq!(diff_one(described, registered()), updated());
+    }
+
+    #[test]
+    fn notices_changed_permissions_and_contexts() {
+        let restricted = definition().default_member_permissions(Permissions::MANAGE_GUILD);
+        assert_eq!(diff_one(restricted, registered()), updated());
+
+        let mut unrestricted = registered();
+        unrestricted["default_member_permissions"] = json!("32");
+        assert_eq!(diff_one(definition(), unrestricted), updated());
+
+        let guild_only = definition().contexts(vec![InteractionContext::Guild]);
+        assert_eq!(diff_one(guild_only, registered()), updated());
+    }
+
+    #[test]
+    fn creates_and_deletes_by_name() {
+        let registered = serde_json::from_value::<Command>(registered()).unwrap();
+        let report = diff(&[CreateCommand::new("browse")], &[registered]);
+        assert_eq!(report.created, ["browse"]);
+        assert_eq!(report.deleted, ["search"]);
+    }
+}
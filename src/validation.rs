@@ -0,0 +1,231 @@
+use std::fmt;
+
+use serenity::all::CreateEmbed;
+use url::Url;
+
+use crate::{Color, GuildEntry, GuildPatch, i18n::Locale, tr};
+
+pub const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    InvalidGuildId,
+    InvalidIconUrl,
+    InvalidInviteUrl,
+    InvalidBannerUrl,
+    InvalidTag,
+    MissingGuildId,
+    MissingName,
+    MissingDescription,
+    MissingLanguage,
+    SetAndCleared,
+    NoChanges,
+}
+
+impl ValidationError {
+    pub fn title(&self, locale: Locale) -> &'static str {
+        match self {
+            Self::InvalidGuildId => tr!(locale, "validation.invalid_guild_id"),
+            Self::InvalidIconUrl => tr!(locale, "validation.invalid_icon_url"),
+            Self::InvalidInviteUrl => tr!(locale, "validation.invalid_invite_url"),
+            Self::InvalidBannerUrl => tr!(locale, "validation.invalid_banner_url"),
+            Self::InvalidTag => tr!(locale, "validation.invalid_tag"),
+            Self::MissingGuildId => tr!(locale, "validation.missing_guild_id"),
+            Self::MissingName => tr!(locale, "validation.missing_name"),
+            Self::MissingDescription => tr!(locale, "validation.missing_description"),
+            Self::MissingLanguage => tr!(locale, "validation.missing_language"),
+            Self::SetAndCleared => tr!(locale, "validation.set_and_cleared"),
+            Self::NoChanges => tr!(locale, "validation.no_changes"),
+        }
+    }
+
+    pub fn create_embed(&self, locale: Locale) -> CreateEmbed {
+        CreateEmbed::new()
+            .color(Color::ERROR)
+            .title(self.title(locale))
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.title(Locale::English))
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+pub fn parse_guild_id(value: &str) -> Result<u64, ValidationError> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| ValidationError::InvalidGuildId)
+}
+
+pub fn parse_icon_url(value: &str) -> Result<Url, ValidationError> {
+    Url::parse(value.trim()).map_err(|_| ValidationError::InvalidIconUrl)
+}
+
+pub fn parse_invite_url(value: &str) -> Result<Url, ValidationError> {
+    Url::parse(value.trim()).map_err(|_| ValidationError::InvalidInviteUrl)
+}
+
+pub fn parse_banner_url(value: &str) -> Result<Url, ValidationError> {
+    Url::parse(value.trim()).map_err(|_| ValidationError::InvalidBannerUrl)
+}
+
+/// Splits comma separated tags, lowercased, sorted and without duplicates.
+pub fn parse_tags(value: &str) -> Result<Vec<String>, ValidationError> {
+    let mut tags = value
+        .split(',')
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<_>>();
+    // Tags end up in the custom IDs of `/browse` buttons, which are limited to 100 characters.
+    if tags.iter().any(|tag| tag.chars().count() > MAX_TAG_LENGTH) {
+        return Err(ValidationError::InvalidTag);
+    }
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+/// User supplied fields of a [`GuildEntry`], as typed into `/add` or `guildly add`, with IDs and
+/// URLs already parsed.
+#[derive(Debug, Default)]
+pub struct EntryInput<'a> {
+    pub guild_id: Option<u64>,
+    pub name: Option<&'a str>,
+    pub icon_url: Option<Url>,
+    pub invite_url: Option<Url>,
+    pub description: Option<&'a str>,
+    pub language: Option<&'a str>,
+    /// Comma separated.
+    pub tags: Option<&'a str>,
+    pub nsfw: bool,
+    pub banner_url: Option<Url>,
+}
+
+impl EntryInput<'_> {
+    pub fn validate(&self) -> Result<GuildEntry, ValidationError> {
+        let guild_id = self.guild_id.ok_or(ValidationError::MissingGuildId)?;
+        let name = self
+            .name
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or(ValidationError::MissingName)?;
+
+        let description = self
+            .description
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+            .map(str::to_string);
+        let language = self
+            .language
+            .map(|language| language.trim().to_lowercase())
+            .filter(|language| !language.is_empty());
+
+        Ok(GuildEntry {
+            name: name.to_string(),
+            guild_id,
+            invite_url: self.invite_url.clone(),
+            icon_url: self.icon_url.clone(),
+            description,
+            language,
+            tags: self.tags.map(parse_tags).transpose()?.unwrap_or_default(),
+            nsfw: self.nsfw,
+            banner_url: self.banner_url.clone(),
+            added_at: None,
+            updated_at: None,
+        })
+    }
+}
+
+/// User supplied changes to a [`GuildEntry`], as typed into `/edit` or `guildly edit`.
+#[derive(Debug, Default)]
+pub struct PatchInput<'a> {
+    pub name: Option<&'a str>,
+    pub icon_url: Option<Url>,
+    pub invite_url: Option<Url>,
+    pub description: Option<&'a str>,
+    pub language: Option<&'a str>,
+    /// Comma separated, replacing every current tag.
+    pub tags: Option<&'a str>,
+    pub nsfw: Option<bool>,
+    pub banner_url: Option<Url>,
+    pub clear_icon: bool,
+    pub clear_invite: bool,
+    pub clear_description: bool,
+    pub clear_language: bool,
+    pub clear_tags: bool,
+    pub clear_banner: bool,
+}
+
+impl PatchInput<'_> {
+    pub fn validate(&self) -> Result<GuildPatch, ValidationError> {
+        if (self.clear_icon && self.icon_url.is_some())
+            || (self.clear_invite && self.invite_url.is_some())
+            || (self.clear_description && self.description.is_some())
+            || (self.clear_language && self.language.is_some())
+            || (self.clear_tags && self.tags.is_some())
+            || (self.clear_banner && self.banner_url.is_some())
+        {
+            return Err(ValidationError::SetAndCleared);
+        }
+
+        let name = match self.name.map(str::trim) {
+            Some("") => return Err(ValidationError::MissingName),
+            name => name.map(str::to_string),
+        };
+        let icon_url = match &self.icon_url {
+            Some(icon_url) => Some(Some(icon_url.clone())),
+            None if self.clear_icon => Some(None),
+            None => None,
+        };
+        let invite_url = match &self.invite_url {
+            Some(invite_url) => Some(Some(invite_url.clone())),
+            None if self.clear_invite => Some(None),
+            None => None,
+        };
+
+        let description = match self.description.map(str::trim) {
+            Some("") => return Err(ValidationError::MissingDescription),
+            Some(description) => Some(Some(description.to_string())),
+            None if self.clear_description => Some(None),
+            None => None,
+        };
+        let language = match self.language.map(|language| language.trim().to_lowercase()) {
+            Some(language) if language.is_empty() => {
+                return Err(ValidationError::MissingLanguage);
+            }
+            Some(language) => Some(Some(language)),
+            None if self.clear_language => Some(None),
+            None => None,
+        };
+        let tags = match self.tags {
+            Some(tags) => Some(parse_tags(tags)?),
+            None if self.clear_tags => Some(Vec::new()),
+            None => None,
+        };
+        let banner_url = match &self.banner_url {
+            Some(banner_url) => Some(Some(banner_url.clone())),
+            None if self.clear_banner => Some(None),
+            None => None,
+        };
+
+        let patch = GuildPatch {
+            name,
+            invite_url,
+            icon_url,
+            description,
+            language,
+            tags,
+            nsfw: self.nsfw,
+            banner_url,
+        };
+        if patch.is_empty() {
+            Err(ValidationError::NoChanges)
+        } else {
+            Ok(patch)
+        }
+    }
+}
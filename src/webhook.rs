@@ -0,0 +1,408 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response as HttpResponse},
+    routing::post,
+};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serenity::all::{CommandInteraction, CreateInteractionResponse, Http, Interaction};
+use tokio::{net::TcpListener, sync::oneshot};
+
+use crate::{GuildlyHandler, response};
+
+const SIGNATURE_HEADER: &str = "X-Signature-Ed25519";
+const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/// How far the signed timestamp may be from the current time, so that captured requests cannot
+/// be replayed later.
+const MAX_TIMESTAMP_SKEW: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    InvalidPublicKey,
+    MissingSignature,
+    InvalidSignature,
+    StaleTimestamp,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPublicKey => f.write_str("public key must be 32 bytes of hex"),
+            Self::MissingSignature => f.write_str("request is not signed"),
+            Self::InvalidSignature => f.write_str("invalid request signature"),
+            Self::StaleTimestamp => f.write_str("request timestamp is too old"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Checks that interaction requests were signed by Discord with the application's public key.
+///
+/// ```
+/// use ed25519_dalek::{Signer, SigningKey};
+/// use guildly::webhook::Verifier;
+///
+/// let signing_key = SigningKey::from_bytes(&[7; 32]);
+/// let verifier = Verifier::from_hex(&hex::encode(signing_key.verifying_key().as_bytes())).unwrap();
+///
+/// let (timestamp, body) = ("1700000000", br#"{"type":1}"#);
+/// let signature = hex::encode(signing_key.sign(&[timestamp.as_bytes(), body].concat()).to_bytes());
+/// assert!(verifier.verify(&signature, timestamp, body).is_ok());
+/// assert!(verifier.verify(&signature, "1700000001", body).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    key: VerifyingKey,
+}
+
+impl Verifier {
+    /// Reads the public key shown in the Developer Portal, or the `verify_key` of the application.
+    pub fn from_hex(public_key: &str) -> Result<Self, VerifyError> {
+        let bytes = hex::decode(public_key.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or(VerifyError::InvalidPublicKey)?;
+        let key = VerifyingKey::from_bytes(&bytes).map_err(|_| VerifyError::InvalidPublicKey)?;
+        Ok(Self { key })
+    }
+
+    /// Verifies the hex `signature` of `timestamp` followed by `body`.
+    pub fn verify(&self, signature: &str, timestamp: &str, body: &[u8]) -> Result<(), VerifyError> {
+        let bytes = hex::decode(signature)
+            .ok()
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .ok_or(VerifyError::InvalidSignature)?;
+        let message = [timestamp.as_bytes(), body].concat();
+        self.key
+            .verify_strict(&message, &Signature::from_bytes(&bytes))
+            .map_err(|_| VerifyError::InvalidSignature)
+    }
+
+    fn verify_headers(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), VerifyError> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or(VerifyError::MissingSignature)
+        };
+        let timestamp = header(TIMESTAMP_HEADER)?;
+        self.verify(header(SIGNATURE_HEADER)?, timestamp, body)?;
+        check_timestamp(timestamp, SystemTime::now())
+    }
+}
+
+/// Checks that the unix `timestamp` a request was signed with is within [`MAX_TIMESTAMP_SKEW`]
+/// of `now`.
+fn check_timestamp(timestamp: &str, now: SystemTime) -> Result<(), VerifyError> {
+    let signed_at = timestamp
+        .parse::<u64>()
+        .map_err(|_| VerifyError::StaleTimestamp)?;
+    let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if now.abs_diff(signed_at) > MAX_TIMESTAMP_SKEW.as_secs() {
+        return Err(VerifyError::StaleTimestamp);
+    }
+    Ok(())
+}
+
+struct ServerState {
+    handler: Arc<GuildlyHandler>,
+    http: Arc<Http>,
+    verifier: Verifier,
+}
+
+/// Routes interactions that Discord posts to `/` to `handler`. `http` must have the application
+/// ID set, since deferred replies and follow-ups are sent through it.
+pub fn router(handler: Arc<GuildlyHandler>, http: Arc<Http>, verifier: Verifier) -> Router {
+    Router::new()
+        .route("/", post(receive))
+        .with_state(Arc::new(ServerState {
+            handler,
+            http,
+            verifier,
+        }))
+}
+
+/// Serves `router` until the process is stopped. Discord only posts to HTTPS endpoints, so
+/// `listener` is meant to sit behind a TLS terminating proxy.
+pub async fn serve(listener: TcpListener, router: Router) -> std::io::Result<()> {
+    axum::serve(listener, router).await
+}
+
+async fn receive(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> HttpResponse {
+    if let Err(why) = state.verifier.verify_headers(&headers, &body) {
+        return (StatusCode::UNAUTHORIZED, why.to_string()).into_response();
+    }
+    let interaction = match serde_json::from_slice::<Interaction>(&body) {
+        Ok(interaction) => interaction,
+        Err(why) => return (StatusCode::BAD_REQUEST, why.to_string()).into_response(),
+    };
+
+    match respond(&state, interaction).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Returns the body of the reply to `interaction`, or `None` if no command, component or modal
+/// handles it.
+async fn respond(
+    state: &ServerState,
+    interaction: Interaction,
+) -> Option<CreateInteractionResponse> {
+    let handler = &state.handler;
+    match interaction {
+        Interaction::Ping(_) => Some(CreateInteractionResponse::Pong),
+        Interaction::Command(command) => {
+            if !handler.commands.contains_key(command.data.name.as_str()) {
+                return None;
+            }
+            let (reply, initial) = oneshot::channel();
+            tokio::spawn(run_command(
+                handler.clone(),
+                state.http.clone(),
+                command,
+                reply,
+            ));
+            initial.await.ok()
+        }
+        Interaction::Component(component) => handler.run_component(&state.http, &component).await,
+        Interaction::Modal(modal) => handler.run_modal(&state.http, &modal).await,
+        _ => None,
+    }
+}
+
+/// Runs a command, passing its initial response to `reply`. Replies that come too late or carry
+/// files are deferred, and then sent through `http` like the follow-ups.
+async fn run_command(
+    handler: Arc<GuildlyHandler>,
+    http: Arc<Http>,
+    command: CommandInteraction,
+    reply: oneshot::Sender<CreateInteractionResponse>,
+) {
+    let registered = &handler.commands[command.data.name.as_str()];
+    let ephemeral = registered.is_ephemeral(&command);
+    let mut reply = Some(reply);
+
+    let deferral = &mut reply;
+    let (response, deferred) = registered
+        .run(&handler, &http, &command, move |ephemeral| async move {
+            send_initial(deferral, response::create_defer(ephemeral));
+            Ok(())
+        })
+        .await;
+
+    let result = if deferred || response.has_attachments() {
+        send_initial(&mut reply, response::create_defer(ephemeral));
+        response.send(&http, &command, true).await
+    } else {
+        let (initial, follow_ups) = response.into_interaction_response();
+        send_initial(&mut reply, initial);
+        response::send_follow_ups(&http, &command, follow_ups).await
+    };
+    if let Err(why) = result {
+        tracing::error!("Failed to respond to {}: {why:?}", command.data.name);
+    }
+}
+
+/// Sends the initial response, unless the interaction has already been deferred.
+fn send_initial(
+    reply: &mut Option<oneshot::Sender<CreateInteractionResponse>>,
+    response: CreateInteractionResponse,
+) {
+    if let Some(reply) = reply.take() {
+        // The request is gone if Discord stopped waiting for it.
+        let _ = reply.send(response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use ed25519_dalek::{Signer, SigningKey};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::database::Database;
+
+    const PING: &str = r#"{"type":1,"id":"1","application_id":"2","token":"token","version":1}"#;
+
+    /// Routes to a handler with the builtin commands and components, listing one `rust` server.
+    fn create_router(key: &SigningKey) -> Router {
+        let database = Database::open(":memory:").unwrap();
+        let entry = r#"{
+            "name": "Rust Community",
+            "guild_id": 10,
+            "invite_url": "https://discord.gg/rust",
+            "icon_url": null,
+            "tags": ["rust"]
+        }"#;
+        database
+            .insert(&serde_json::from_str(entry).unwrap())
+            .unwrap();
+        let handler = GuildlyHandler::builder(database).build();
+        let verifier = Verifier::from_hex(&hex::encode(key.verifying_key().as_bytes())).unwrap();
+        router(Arc::new(handler), Arc::new(Http::new("")), verifier)
+    }
+
+    fn now() -> String {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        now.as_secs().to_string()
+    }
+
+    /// Posts `body` signed by `key` at `timestamp`, or unsigned if `key` is `None`.
+    async fn post(
+        router: Router,
+        key: Option<&SigningKey>,
+        timestamp: &str,
+        body: &str,
+    ) -> (StatusCode, String) {
+        let mut request = Request::post("/").header("Content-Type", "application/json");
+        if let Some(key) = key {
+            let signature = key.sign(&[timestamp.as_bytes(), body.as_bytes()].concat());
+            request = request
+                .header(SIGNATURE_HEADER, hex::encode(signature.to_bytes()))
+                .header(TIMESTAMP_HEADER, timestamp);
+        }
+        let response = router
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn answers_pings_with_pongs() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let (status, body) = post(create_router(&key), Some(&key), &now(), PING).await;
+        assert_eq!(status, StatusCode::OK);
+        let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["type"], 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_unverified_requests() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other = SigningKey::from_bytes(&[8; 32]);
+        let stale = (now().parse::<u64>().unwrap() - 600).to_string();
+        for (signer, timestamp) in [(None, now()), (Some(&other), now()), (Some(&key), stale)] {
+            let (status, _) = post(create_router(&key), signer, &timestamp, PING).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_answer_unknown_commands() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let command = r#"{
+            "type": 2,
+            "id": "1",
+            "application_id": "2",
+            "token": "token",
+            "version": 1,
+            "locale": "en-US",
+            "channel_id": "3",
+            "user": {"id": "4", "username": "user", "discriminator": "0", "avatar": null},
+            "data": {"id": "5", "name": "unknown", "type": 1},
+            "entitlements": [],
+            "authorizing_integration_owners": {},
+            "app_permissions": "0"
+        }"#;
+        let (status, body) = post(create_router(&key), Some(&key), &now(), command).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+    }
+
+    #[tokio::test]
+    async fn answers_registered_commands() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let command = r#"{
+            "type": 2,
+            "id": "1",
+            "application_id": "2",
+            "token": "token",
+            "version": 1,
+            "locale": "en-US",
+            "channel_id": "3",
+            "user": {"id": "4", "username": "user", "discriminator": "0", "avatar": null},
+            "data": {
+                "id": "5",
+                "name": "search",
+                "type": 1,
+                "options": [{"name": "id", "type": 3, "value": "10"}]
+            },
+            "entitlements": [],
+            "authorizing_integration_owners": {},
+            "app_permissions": "0"
+        }"#;
+        let (status, body) = post(create_router(&key), Some(&key), &now(), command).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["type"], 4);
+        assert_eq!(
+            body["data"]["embeds"][0]["author"]["name"],
+            "Rust Community"
+        );
+    }
+
+    #[tokio::test]
+    async fn answers_components() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let component = r#"{
+            "type": 3,
+            "id": "1",
+            "application_id": "2",
+            "token": "token",
+            "version": 1,
+            "locale": "en-US",
+            "channel_id": "3",
+            "user": {"id": "4", "username": "user", "discriminator": "0", "avatar": null},
+            "data": {"custom_id": "guildly:browse:page:0:rust", "component_type": 2},
+            "message": {
+                "id": "6",
+                "channel_id": "3",
+                "author": {"id": "2", "username": "guildly", "discriminator": "0", "avatar": null},
+                "content": "",
+                "timestamp": "2024-01-01T00:00:00+00:00",
+                "edited_timestamp": null,
+                "tts": false,
+                "mention_everyone": false,
+                "mentions": [],
+                "mention_roles": [],
+                "attachments": [],
+                "embeds": [],
+                "pinned": false,
+                "type": 0
+            },
+            "entitlements": [],
+            "authorizing_integration_owners": {},
+            "app_permissions": "0"
+        }"#;
+        let (status, body) = post(create_router(&key), Some(&key), &now(), component).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["type"], 7);
+        assert!(
+            body["data"]["embeds"][0]
+                .to_string()
+                .contains("Rust Community"),
+            "{body}"
+        );
+    }
+}